@@ -0,0 +1,520 @@
+use crate::matching::data::{MatchingData, MatchingResult, RawValue};
+use indexmap::IndexMap;
+use std::fmt;
+
+type Elements = IndexMap<String, IndexMap<String, RawValue>>; // element id -> field id -> value
+
+const DELIMITERS: [u8; 4] = [b',', b';', b'\t', b'|'];
+const QUOTES: [u8; 2] = [b'"', b'\''];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct CsvOptions {
+    pub delimiter: Option<u8>, // detected from the text if not set
+    pub quote: Option<u8>,     // detected from the text if not set
+    pub has_headers: bool,
+}
+
+impl Default for CsvOptions {
+    fn default() -> Self {
+        Self {
+            delimiter: None,
+            quote: None,
+            has_headers: true,
+        }
+    }
+}
+
+/// Cells of an imported CSV file or spreadsheet, all rows have as many cells as there are headers.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Table {
+    pub headers: Vec<String>,
+    pub rows: Vec<Vec<String>>,
+}
+
+impl Table {
+    /// Pads the rows and skips empty ones, headers are numbered if the first row does not hold them.
+    pub fn from_rows(rows: Vec<Vec<String>>, has_headers: bool) -> Self {
+        let mut rows: Vec<Vec<String>> = rows
+            .into_iter()
+            .filter(|row| row.iter().any(|cell| !cell.is_empty()))
+            .collect();
+        let columns = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        for row in rows.iter_mut() {
+            row.resize(columns, "".to_string());
+        }
+        let headers = if has_headers && !rows.is_empty() {
+            rows.remove(0)
+        } else {
+            (1..=columns)
+                .map(|column| format!("Column {}", column))
+                .collect()
+        };
+        return Self { headers, rows };
+    }
+}
+
+pub fn read_table(text: &str, options: &CsvOptions) -> Result<Table, String> {
+    let text = text.trim_start_matches('\u{feff}');
+    let quote = options.quote.unwrap_or_else(|| detect_quote(text));
+    let delimiter = options
+        .delimiter
+        .unwrap_or_else(|| detect_delimiter(text, quote));
+    let mut reader = ::csv::ReaderBuilder::new()
+        .delimiter(delimiter)
+        .quote(quote)
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(text.as_bytes());
+    let mut rows = Vec::new();
+    for record in reader.records() {
+        let record = record.map_err(|error| format!("CSV is not well-formatted: {}", error))?;
+        rows.push(record.iter().map(|cell| cell.trim().to_string()).collect());
+    }
+    return Ok(Table::from_rows(rows, options.has_headers));
+}
+
+/// The delimiter occurring most consistently in the first lines, ignoring quoted text.
+pub fn detect_delimiter(text: &str, quote: u8) -> u8 {
+    let lines: Vec<&str> = text
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .take(10)
+        .collect();
+    let mut best = (b',', 0);
+    for delimiter in DELIMITERS {
+        let counts: Vec<usize> = lines
+            .iter()
+            .map(|line| count_unquoted(line, delimiter, quote))
+            .collect();
+        let min = counts.iter().cloned().min().unwrap_or(0);
+        let consistent = counts.iter().all(|count| *count == counts[0]);
+        let rating = if consistent { min * 2 + 1 } else { min * 2 };
+        if min > 0 && rating > best.1 {
+            best = (delimiter, rating);
+        }
+    }
+    return best.0;
+}
+
+/// Single quotes are only used if more fields start with them than with double quotes.
+pub fn detect_quote(text: &str) -> u8 {
+    let mut best = (b'"', 0);
+    for quote in QUOTES {
+        let opened = text
+            .lines()
+            .take(10)
+            .map(|line| {
+                let bytes = line.trim_start().as_bytes();
+                (0..bytes.len())
+                    .filter(|index| {
+                        bytes[*index] == quote
+                            && (*index == 0 || DELIMITERS.contains(&bytes[*index - 1]))
+                    })
+                    .count()
+            })
+            .sum::<usize>();
+        if opened > best.1 {
+            best = (quote, opened);
+        }
+    }
+    return best.0;
+}
+
+fn count_unquoted(line: &str, delimiter: u8, quote: u8) -> usize {
+    let mut quoted = false;
+    let mut count = 0;
+    for byte in line.bytes() {
+        if byte == quote {
+            quoted = !quoted;
+        } else if byte == delimiter && !quoted {
+            count += 1;
+        }
+    }
+    return count;
+}
+
+/// Which column holds the element ids and which field every column is imported into.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ColumnMapping {
+    pub id_column: Option<usize>,    // ids are generated if not set
+    pub fields: Vec<Option<String>>, // field id per column, columns without one are skipped
+}
+
+impl ColumnMapping {
+    /// Maps columns to the existing field with the same name, or to a new field named like the header.
+    pub fn guess(table: &Table, fields: &IndexMap<String, String>) -> Self {
+        let id_column = table.headers.iter().position(|header| {
+            ["id", "nr", "no", "number"].contains(&&*header.trim().to_lowercase())
+        });
+        let mut new_fields = fields.clone();
+        let mut mapping = Vec::new();
+        for (column, header) in table.headers.iter().enumerate() {
+            if Some(column) == id_column {
+                mapping.push(None);
+                continue;
+            }
+            let existing = fields
+                .iter()
+                .find(|(_, name)| name.trim().to_lowercase() == header.trim().to_lowercase())
+                .map(|(id, _)| id.clone());
+            mapping.push(Some(match existing {
+                Some(id) => id,
+                None => {
+                    let id = next_field_id(&new_fields);
+                    new_fields.insert(id.clone(), header.clone());
+                    id
+                }
+            }));
+        }
+        return Self {
+            id_column,
+            fields: mapping,
+        };
+    }
+}
+
+/// Field ids follow the pattern of the existing data, "group1", "group2", ...
+pub fn next_field_id(fields: &IndexMap<String, String>) -> String {
+    let mut number = fields.len() + 1;
+    while fields.contains_key(&format!("group{}", number)) {
+        number += 1;
+    }
+    return format!("group{}", number);
+}
+
+/// Builds the fields and elements of the table, fields not mapped to any column are kept.
+pub fn to_elements(
+    table: &Table,
+    mapping: &ColumnMapping,
+    fields: &IndexMap<String, String>,
+) -> Result<(IndexMap<String, String>, Elements), String> {
+    let mut new_fields = fields.clone();
+    for (column, field) in mapping.fields.iter().enumerate() {
+        if let Some(field) = field {
+            if !new_fields.contains_key(field) {
+                new_fields.insert(field.clone(), table.headers[column].clone());
+            }
+        }
+    }
+    let width = table.rows.len().to_string().len().max(2);
+    let mut elements = IndexMap::new();
+    for (index, row) in table.rows.iter().enumerate() {
+        let id = match mapping.id_column {
+            Some(column) => row[column].clone(),
+            None => format!("{:0width$}", index + 1, width = width),
+        };
+        if id.is_empty() {
+            return Err(format!("Row {} has no id", index + 1));
+        }
+        let mut element = IndexMap::new();
+        for (column, field) in mapping.fields.iter().enumerate() {
+            if let Some(field) = field {
+                if Some(column) != mapping.id_column {
+                    element.insert(field.clone(), RawValue::Text(row[column].clone()));
+                }
+            }
+        }
+        if elements.insert(id.clone(), element).is_some() {
+            return Err(format!("The id {} is used more than once", id));
+        }
+    }
+    return Ok((new_fields, elements));
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExportLayout {
+    Wide, // one row per group
+    Long, // one row per member
+}
+
+impl ExportLayout {
+    pub fn values() -> Vec<Self> {
+        return vec![ExportLayout::Wide, ExportLayout::Long];
+    }
+}
+
+impl fmt::Display for ExportLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ExportOptions {
+    pub layout: ExportLayout,
+    pub group_number: bool,
+    pub group_score: bool,
+    pub member_ids: bool,
+    pub fields: Vec<String>, // field ids exported for every member
+    pub delimiter: u8,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            layout: ExportLayout::Wide,
+            group_number: true,
+            group_score: false,
+            member_ids: true,
+            fields: Vec::new(),
+            delimiter: b',',
+        }
+    }
+}
+
+pub fn write_result(
+    result: &MatchingResult,
+    data: &MatchingData,
+    options: &ExportOptions,
+) -> Result<String, String> {
+    let mut writer = ::csv::WriterBuilder::new()
+        .delimiter(options.delimiter)
+        .flexible(true)
+        .from_writer(Vec::new());
+    for row in result_rows(result, data, options) {
+        writer
+            .write_record(&row)
+            .map_err(|error| error.to_string())?;
+    }
+    let bytes = writer.into_inner().map_err(|error| error.to_string())?;
+    return String::from_utf8(bytes).map_err(|error| error.to_string());
+}
+
+/// Header row and the groups of a result, in the wide layout members of a group follow each other in one row.
+pub fn result_rows(
+    result: &MatchingResult,
+    data: &MatchingData,
+    options: &ExportOptions,
+) -> Vec<Vec<String>> {
+    let member_headers = |prefix: &str| {
+        let mut headers = Vec::new();
+        if options.member_ids {
+            headers.push(format!("{}Id", prefix));
+        }
+        for field in &options.fields {
+            let name = data.fields.get(field).unwrap_or(field);
+            headers.push(format!("{}{}", prefix, name));
+        }
+        return headers;
+    };
+    let member_values = |id: &String| {
+        let mut values = Vec::new();
+        if options.member_ids {
+            values.push(id.clone());
+        }
+        for field in &options.fields {
+            values.push(
+                data.elements
+                    .get(id)
+                    .and_then(|element| element.get(field))
+                    .map(|value| value.to_string())
+                    .unwrap_or_default(),
+            );
+        }
+        return values;
+    };
+    let group_values = |index: usize| {
+        let mut values = Vec::new();
+        if options.group_number {
+            values.push((index + 1).to_string());
+        }
+        if options.group_score {
+            values.push(
+                result
+                    .groups
+                    .get(index)
+                    .map(|group| group.score.to_string())
+                    .unwrap_or_default(),
+            );
+        }
+        return values;
+    };
+    let mut headers = Vec::new();
+    if options.group_number {
+        headers.push("Group".to_string());
+    }
+    if options.group_score {
+        headers.push("Score".to_string());
+    }
+    let mut rows: Vec<Vec<String>> = Vec::new();
+    match options.layout {
+        ExportLayout::Wide => {
+            let max_size = result
+                .connections
+                .iter()
+                .map(|group| group.len())
+                .max()
+                .unwrap_or(0);
+            for member in 1..=max_size {
+                headers.extend(member_headers(&format!("Member {} ", member)));
+            }
+            for (index, group) in result.connections.iter().enumerate() {
+                let mut row = group_values(index);
+                for id in group {
+                    row.extend(member_values(id));
+                }
+                rows.push(row);
+            }
+        }
+        ExportLayout::Long => {
+            headers.extend(member_headers(""));
+            for (index, group) in result.connections.iter().enumerate() {
+                for id in group {
+                    let mut row = group_values(index);
+                    row.extend(member_values(id));
+                    rows.push(row);
+                }
+            }
+        }
+    }
+    rows.insert(0, headers);
+    return rows;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fields(names: &[(&str, &str)]) -> IndexMap<String, String> {
+        return names
+            .iter()
+            .map(|(id, name)| (id.to_string(), name.to_string()))
+            .collect();
+    }
+
+    #[test]
+    fn delimiter_and_quote_are_detected() {
+        assert_eq!(detect_delimiter("a;b;c\n1;2;3\n", b'"'), b';');
+        assert_eq!(detect_delimiter("a\tb\n1\t2\n", b'"'), b'\t');
+        assert_eq!(detect_delimiter("\"a,b\";c\n\"1,2\";3\n", b'"'), b';');
+        assert_eq!(detect_delimiter("single column\n", b'"'), b',');
+        assert_eq!(detect_quote("'a;b';c\n'1';2\n"), b'\'');
+        assert_eq!(detect_quote("\"a\",b\nit's,2\n"), b'"');
+    }
+
+    #[test]
+    fn tables_are_read_with_or_without_headers() {
+        let text = "\u{feff}Name; Team \n Tim ;Devs\n\n Gabi;\n";
+        let table = read_table(text, &CsvOptions::default()).unwrap();
+        assert_eq!(table.headers, vec!["Name", "Team"]);
+        assert_eq!(table.rows, vec![vec!["Tim", "Devs"], vec!["Gabi", ""]]);
+        let options = CsvOptions {
+            has_headers: false,
+            ..CsvOptions::default()
+        };
+        let table = read_table("Tim,Devs\n", &options).unwrap();
+        assert_eq!(table.headers, vec!["Column 1", "Column 2"]);
+        assert_eq!(table.rows.len(), 1);
+    }
+
+    #[test]
+    fn columns_are_mapped_to_fields_with_the_same_name() {
+        let table = read_table(
+            "Nr,name,Team\n1,Tim,Devs\n2,Gabi,Chef\n",
+            &CsvOptions::default(),
+        )
+        .unwrap();
+        let existing = fields(&[("group1", "Name"), ("group2", "Standort")]);
+        let mapping = ColumnMapping::guess(&table, &existing);
+        assert_eq!(mapping.id_column, Some(0));
+        assert_eq!(
+            mapping.fields,
+            vec![None, Some("group1".to_string()), Some("group3".to_string())]
+        );
+        let (new_fields, elements) = to_elements(&table, &mapping, &existing).unwrap();
+        assert_eq!(
+            new_fields,
+            fields(&[
+                ("group1", "Name"),
+                ("group2", "Standort"),
+                ("group3", "Team")
+            ])
+        );
+        assert_eq!(elements.keys().collect::<Vec<&String>>(), vec!["1", "2"]);
+        assert_eq!(
+            elements["2"].get("group3"),
+            Some(&RawValue::Text("Chef".to_string()))
+        );
+        assert_eq!(elements["2"].get("group2"), None);
+    }
+
+    #[test]
+    fn ids_are_generated_or_must_be_unique() {
+        let table = read_table("Name\nTim\nGabi\n", &CsvOptions::default()).unwrap();
+        let mapping = ColumnMapping::guess(&table, &IndexMap::new());
+        let (_, elements) = to_elements(&table, &mapping, &IndexMap::new()).unwrap();
+        assert_eq!(elements.keys().collect::<Vec<&String>>(), vec!["01", "02"]);
+
+        let table = read_table("Id,Name\n1,Tim\n1,Gabi\n", &CsvOptions::default()).unwrap();
+        let mapping = ColumnMapping::guess(&table, &IndexMap::new());
+        assert!(to_elements(&table, &mapping, &IndexMap::new()).is_err());
+    }
+
+    #[test]
+    fn field_ids_continue_the_numbering() {
+        assert_eq!(next_field_id(&IndexMap::new()), "group1");
+        assert_eq!(
+            next_field_id(&fields(&[("group1", "A"), ("group3", "B")])),
+            "group4"
+        );
+    }
+
+    fn grouping() -> (MatchingResult, MatchingData) {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let result = MatchingResult {
+            score: 3,
+            connections: vec![
+                vec!["01".to_string(), "02".to_string()],
+                vec!["04".to_string()],
+            ],
+            groups: Vec::new(),
+            metrics: Vec::new(),
+            violations: Vec::new(),
+        };
+        return (result, data);
+    }
+
+    #[test]
+    fn wide_layout_has_one_row_per_group() {
+        let (result, data) = grouping();
+        let options = ExportOptions {
+            fields: vec!["group1".to_string()],
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            result_rows(&result, &data, &options),
+            vec![
+                vec![
+                    "Group",
+                    "Member 1 Id",
+                    "Member 1 Name",
+                    "Member 2 Id",
+                    "Member 2 Name"
+                ],
+                vec!["1", "01", "Tim", "02", "Gabi"],
+                vec!["2", "04", "Mama"],
+            ]
+        );
+        assert_eq!(
+            write_result(&result, &data, &options).unwrap(),
+            "Group,Member 1 Id,Member 1 Name,Member 2 Id,Member 2 Name\n1,01,Tim,02,Gabi\n2,04,Mama\n"
+        );
+    }
+
+    #[test]
+    fn long_layout_has_one_row_per_member() {
+        let (result, data) = grouping();
+        let options = ExportOptions {
+            layout: ExportLayout::Long,
+            member_ids: false,
+            fields: vec!["group1".to_string(), "group3".to_string()],
+            delimiter: b';',
+            ..ExportOptions::default()
+        };
+        assert_eq!(
+            write_result(&result, &data, &options).unwrap(),
+            "Group;Name;Standort\n1;Tim;Bonn\n1;Gabi;Bonn\n2;Mama;Aachen\n"
+        );
+    }
+}
@@ -0,0 +1,241 @@
+use crate::io::xml_escape;
+use crate::matching::data::{MatchingData, MatchingResult};
+use crate::matching::graph::{EdgeKind, GraphEdge, PreferenceGraph};
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum GraphFormat {
+    Dot,     // Graphviz
+    GraphMl, // yEd, Gephi, Cytoscape
+}
+
+impl GraphFormat {
+    pub fn values() -> Vec<Self> {
+        return vec![GraphFormat::Dot, GraphFormat::GraphMl];
+    }
+
+    pub fn extension(&self) -> &'static str {
+        return match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::GraphMl => "graphml",
+        };
+    }
+
+    pub fn mime(&self) -> &'static str {
+        return match self {
+            GraphFormat::Dot => "text/vnd.graphviz",
+            GraphFormat::GraphMl => "application/graphml+xml",
+        };
+    }
+}
+
+impl fmt::Display for GraphFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Writes the graph with the element names as labels. If a result is given the nodes are clustered by its groups,
+/// elements that are in none of them stay outside of the clusters.
+pub fn write_graph(
+    graph: &PreferenceGraph,
+    data: &MatchingData,
+    result: Option<&MatchingResult>,
+    format: GraphFormat,
+) -> String {
+    let groups: Vec<Vec<String>> = match result {
+        Some(result) => result.connections.clone(),
+        None => Vec::new(),
+    };
+    let ungrouped: Vec<&String> = graph
+        .nodes
+        .iter()
+        .filter(|node| !groups.iter().any(|group| group.contains(node)))
+        .collect();
+    return match format {
+        GraphFormat::Dot => write_dot(graph, data, &groups, &ungrouped),
+        GraphFormat::GraphMl => write_graphml(graph, data, &groups, &ungrouped),
+    };
+}
+
+fn write_dot(
+    graph: &PreferenceGraph,
+    data: &MatchingData,
+    groups: &[Vec<String>],
+    ungrouped: &Vec<&String>,
+) -> String {
+    let node = |id: &String| {
+        format!(
+            "{} [label={}];\n",
+            dot_string(id),
+            dot_string(&data.element_label(id))
+        )
+    };
+    let mut dot = String::from("digraph preferences {\n    node [shape=box];\n");
+    for (index, group) in groups.iter().enumerate() {
+        dot += &format!(
+            "    subgraph cluster_{} {{\n        label=\"Group {}\";\n",
+            index + 1,
+            index + 1
+        );
+        for id in group {
+            dot += &format!("        {}", node(id));
+        }
+        dot += "    }\n";
+    }
+    for id in ungrouped {
+        dot += &format!("    {}", node(id));
+    }
+    for edge in &graph.edges {
+        dot += &format!(
+            "    {} -> {} [{}];\n",
+            dot_string(&edge.source),
+            dot_string(&edge.target),
+            dot_style(edge)
+        );
+    }
+    dot += "}\n";
+    return dot;
+}
+
+fn dot_style(edge: &GraphEdge) -> String {
+    let style = match edge.kind {
+        EdgeKind::Required => "color=blue, style=bold",
+        EdgeKind::Excluded => "color=red, style=dashed",
+        EdgeKind::Preferred => "color=darkgreen",
+        EdgeKind::Avoided => "color=orange, style=dotted",
+    };
+    let label = match edge.score {
+        Some(score) => score.to_string(),
+        None => edge.kind.to_string().to_lowercase(),
+    };
+    let direction = if edge.mutual { ", dir=none" } else { "" };
+    return format!("{}, label=\"{}\"{}", style, label, direction);
+}
+
+/// Quotes an id or label, so any text can be used.
+fn dot_string(text: &str) -> String {
+    return format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+/// Groups become nested graphs inside of a node per group, which yEd shows as folders.
+fn write_graphml(
+    graph: &PreferenceGraph,
+    data: &MatchingData,
+    groups: &[Vec<String>],
+    ungrouped: &Vec<&String>,
+) -> String {
+    let node = |id: &String, group: Option<usize>| {
+        let group = match group {
+            Some(group) => format!("<data key=\"group\">{}</data>", group),
+            None => "".to_string(),
+        };
+        format!(
+            "<node id=\"{}\"><data key=\"label\">{}</data>{}</node>\n",
+            xml_escape(id),
+            xml_escape(&data.element_label(id)),
+            group
+        )
+    };
+    let mut xml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+        "  <key id=\"label\" for=\"node\" attr.name=\"label\" attr.type=\"string\"/>\n",
+        "  <key id=\"group\" for=\"node\" attr.name=\"group\" attr.type=\"int\"/>\n",
+        "  <key id=\"kind\" for=\"edge\" attr.name=\"kind\" attr.type=\"string\"/>\n",
+        "  <key id=\"score\" for=\"edge\" attr.name=\"score\" attr.type=\"int\"/>\n",
+        "  <graph id=\"preferences\" edgedefault=\"directed\">\n",
+    ));
+    for (index, group) in groups.iter().enumerate() {
+        xml += &format!(
+            "    <node id=\"group-{0}\"><data key=\"label\">Group {0}</data>\n      <graph id=\"group-{0}:\" edgedefault=\"directed\">\n",
+            index + 1
+        );
+        for id in group {
+            xml += &format!("        {}", node(id, Some(index + 1)));
+        }
+        xml += "      </graph>\n    </node>\n";
+    }
+    for id in ungrouped {
+        xml += &format!("    {}", node(id, None));
+    }
+    for (index, edge) in graph.edges.iter().enumerate() {
+        let score = match edge.score {
+            Some(score) => format!("<data key=\"score\">{}</data>", score),
+            None => "".to_string(),
+        };
+        xml += &format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\" directed=\"{}\"><data key=\"kind\">{}</data>{}</edge>\n",
+            index,
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            !edge.mutual,
+            edge.kind,
+            score
+        );
+    }
+    xml += "  </graph>\n</graphml>\n";
+    return xml;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::preference_graph;
+
+    fn concept() -> (PreferenceGraph, MatchingData, MatchingResult) {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let graph = preference_graph(&data).unwrap();
+        let result = MatchingResult {
+            score: 0,
+            connections: vec![
+                vec!["01".to_string(), "02".to_string()],
+                vec!["04".to_string(), "05".to_string()],
+            ],
+            groups: Vec::new(),
+            metrics: Vec::new(),
+            violations: Vec::new(),
+        };
+        return (graph, data, result);
+    }
+
+    #[test]
+    fn dot_clusters_the_groups() {
+        let (graph, data, result) = concept();
+        let dot = write_graph(&graph, &data, Some(&result), GraphFormat::Dot);
+        assert!(dot.starts_with("digraph preferences {"));
+        assert!(dot.contains(
+            "    subgraph cluster_1 {\n        label=\"Group 1\";\n        \"01\" [label=\"01 Tim\"];\n"
+        ));
+        assert!(dot.contains("    \"03\" [label=\"03 Tobias\"];\n"));
+        assert!(dot.contains(
+            "    \"01\" -> \"04\" [color=red, style=dashed, label=\"excluded\", dir=none];\n"
+        ));
+        assert!(dot.contains("    \"01\" -> \"06\" [color=orange, style=dotted, label=\"-1\"];\n"));
+
+        let dot = write_graph(&graph, &data, None, GraphFormat::Dot);
+        assert!(!dot.contains("subgraph"));
+        assert_eq!(dot.matches("[label=").count(), graph.nodes.len());
+    }
+
+    #[test]
+    fn graphml_nests_the_groups() {
+        let (graph, data, result) = concept();
+        let xml = write_graph(&graph, &data, Some(&result), GraphFormat::GraphMl);
+        assert!(xml.contains("<node id=\"group-2\"><data key=\"label\">Group 2</data>"));
+        assert!(xml.contains(
+            "<node id=\"05\"><data key=\"label\">05 Papa</data><data key=\"group\">2</data></node>"
+        ));
+        assert!(xml.contains("<node id=\"06\"><data key=\"label\">06 Thomas</data></node>"));
+        assert!(xml.contains(
+            "source=\"01\" target=\"06\" directed=\"true\"><data key=\"kind\">Avoided</data><data key=\"score\">-1</data>"
+        ));
+        assert_eq!(xml.matches("<edge ").count(), graph.edges.len());
+    }
+
+    #[test]
+    fn labels_are_quoted() {
+        assert_eq!(dot_string("say \"hi\" \\o/"), "\"say \\\"hi\\\" \\\\o/\"");
+    }
+}
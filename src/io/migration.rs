@@ -0,0 +1,92 @@
+use serde_json::{Map, Value};
+
+/// Format version written by this build, documented by res/project.schema.json.
+pub const CURRENT_VERSION: u64 = 1;
+
+/// Upgrades a document by one version and describes what changed.
+type Migration = fn(&mut Map<String, Value>) -> Vec<String>;
+
+/// Migration at index n upgrades version n to n + 1, version 0 are the files written before versions were introduced.
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [unversioned_to_v1];
+
+/// Format version of a document, documents without one are version 0.
+pub fn version_of(document: &Map<String, Value>) -> Result<u64, String> {
+    return match document.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .ok_or(format!("The version {} is not a number", version)),
+    };
+}
+
+/// Upgrades a document to the current version, returning a notice for every change made.
+pub fn migrate(document: &mut Map<String, Value>) -> Result<Vec<String>, String> {
+    let version = version_of(document)?;
+    if version > CURRENT_VERSION {
+        return Err(format!(
+            "The file has version {}, only versions up to {} are supported",
+            version, CURRENT_VERSION
+        ));
+    }
+    let mut notices = Vec::new();
+    for (from, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        notices.extend(
+            migration(document)
+                .into_iter()
+                .map(|notice| format!("Version {} to {}: {}", from, from + 1, notice)),
+        );
+        document.insert("version".to_string(), Value::from(from + 1));
+    }
+    return Ok(notices);
+}
+
+/// Adds the version to the document, which is the current format otherwise.
+pub fn stamp(document: &mut Map<String, Value>) {
+    document.insert("version".to_string(), Value::from(CURRENT_VERSION));
+}
+
+fn unversioned_to_v1(_document: &mut Map<String, Value>) -> Vec<String> {
+    return vec![
+        "The file has no format version, it is saved with a version from now on".to_string(),
+    ];
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn document(value: Value) -> Map<String, Value> {
+        return match value {
+            Value::Object(document) => document,
+            _ => panic!("not an object"),
+        };
+    }
+
+    #[test]
+    fn unversioned_files_are_upgraded() {
+        let mut document = document(json!({ "fields": {} }));
+        assert_eq!(version_of(&document), Ok(0));
+        let notices = migrate(&mut document).unwrap();
+        assert_eq!(notices.len(), 1);
+        assert!(notices[0].starts_with("Version 0 to 1: "));
+        assert_eq!(version_of(&document), Ok(CURRENT_VERSION));
+    }
+
+    #[test]
+    fn current_files_are_kept() {
+        let mut document = document(json!({ "fields": {} }));
+        stamp(&mut document);
+        let before = document.clone();
+        assert_eq!(migrate(&mut document), Ok(Vec::new()));
+        assert_eq!(document, before);
+    }
+
+    #[test]
+    fn newer_and_invalid_versions_are_rejected() {
+        let mut newer = document(json!({ "version": CURRENT_VERSION + 1 }));
+        assert!(migrate(&mut newer).is_err());
+        let mut invalid = document(json!({ "version": "one" }));
+        assert!(migrate(&mut invalid).is_err());
+    }
+}
@@ -0,0 +1,22 @@
+pub mod csv;
+pub mod graph;
+pub mod migration;
+pub mod pdf;
+pub mod project;
+pub mod report;
+pub mod scores;
+pub mod spreadsheet;
+
+/// Escapes text for XML content and attribute values.
+pub fn xml_escape(text: &str) -> String {
+    return text
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;");
+}
+
+/// Embeds a file into a link, so it can be downloaded without a server.
+pub fn data_url(mime: &str, content: &[u8]) -> String {
+    return format!("data:{};base64,{}", mime, base64::encode(content));
+}
@@ -0,0 +1,430 @@
+use crate::matching::data::{MatchingData, MatchingResult};
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str};
+use std::fmt;
+
+const A4: (f32, f32) = (595.0, 842.0); // points
+const MARGIN: f32 = 56.0;
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PdfLayout {
+    GroupPages, // one page per group listing its members
+    NameTents,  // one folded card per member, name on both halves
+    Overview,   // all groups in one list, continued over as many pages as needed
+}
+
+impl PdfLayout {
+    pub fn values() -> Vec<Self> {
+        return vec![
+            PdfLayout::GroupPages,
+            PdfLayout::NameTents,
+            PdfLayout::Overview,
+        ];
+    }
+}
+
+impl fmt::Display for PdfLayout {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PdfOptions {
+    pub layout: PdfLayout,
+    pub title: String,       // printed above every group, empty for none
+    pub fields: Vec<String>, // field ids printed below the name of every member
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        Self {
+            layout: PdfLayout::GroupPages,
+            title: "".to_string(),
+            fields: Vec::new(),
+        }
+    }
+}
+
+/// Member of a group as printed: the name and the values of the chosen fields.
+struct Member {
+    name: String,
+    details: Vec<String>,
+}
+
+/// Lays out the result with the standard Helvetica fonts, so no font has to be embedded.
+pub fn write_pdf(result: &MatchingResult, data: &MatchingData, options: &PdfOptions) -> Vec<u8> {
+    let groups: Vec<Vec<Member>> = result
+        .connections
+        .iter()
+        .map(|group| group.iter().map(|id| member(data, id, options)).collect())
+        .collect();
+    let pages = match options.layout {
+        PdfLayout::GroupPages => group_pages(&groups, &options.title),
+        PdfLayout::NameTents => name_tents(&groups, &options.title),
+        PdfLayout::Overview => overview(&groups, &options.title),
+    };
+    let catalog_id = Ref::new(1);
+    let tree_id = Ref::new(2);
+    let regular_id = Ref::new(3);
+    let bold_id = Ref::new(4);
+    let mut pdf = Pdf::new();
+    pdf.catalog(catalog_id).pages(tree_id);
+    let page_ids: Vec<Ref> = (0..pages.len())
+        .map(|index| Ref::new(5 + 2 * index as i32))
+        .collect();
+    pdf.pages(tree_id)
+        .kids(page_ids.iter().copied())
+        .count(pages.len() as i32);
+    for (page_id, content) in page_ids.iter().zip(pages) {
+        let content_id = Ref::new(page_id.get() + 1);
+        let mut page = pdf.page(*page_id);
+        page.media_box(Rect::new(0.0, 0.0, A4.0, A4.1));
+        page.parent(tree_id);
+        page.contents(content_id);
+        let mut resources = page.resources();
+        resources
+            .fonts()
+            .pair(REGULAR, regular_id)
+            .pair(BOLD, bold_id);
+        resources.finish();
+        page.finish();
+        pdf.stream(content_id, &content.finish());
+    }
+    pdf.type1_font(regular_id)
+        .base_font(Name(b"Helvetica"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    pdf.type1_font(bold_id)
+        .base_font(Name(b"Helvetica-Bold"))
+        .encoding_predefined(Name(b"WinAnsiEncoding"));
+    return pdf.finish();
+}
+
+fn member(data: &MatchingData, id: &String, options: &PdfOptions) -> Member {
+    let element = data.elements.get(id);
+    let value_of = |field: &String| {
+        element
+            .and_then(|element| element.get(field))
+            .map(|value| value.to_string())
+            .unwrap_or_default()
+    };
+    let name = match data.name_field() {
+        Some(field) if !value_of(field).is_empty() => value_of(field),
+        _ => id.clone(),
+    };
+    return Member {
+        name,
+        details: options
+            .fields
+            .iter()
+            .map(value_of)
+            .filter(|value| !value.is_empty())
+            .collect(),
+    };
+}
+
+/// One page per group, large groups continue on the next page.
+fn group_pages(groups: &[Vec<Member>], title: &str) -> Vec<Content> {
+    let mut pages = Vec::new();
+    for (index, group) in groups.iter().enumerate() {
+        let mut content = Content::new();
+        let mut y = A4.1 - MARGIN;
+        if !title.is_empty() {
+            y -= 14.0;
+            text(&mut content, REGULAR, 14.0, MARGIN, y, title);
+        }
+        y -= 40.0;
+        text(
+            &mut content,
+            BOLD,
+            32.0,
+            MARGIN,
+            y,
+            &format!("Group {}", index + 1),
+        );
+        y -= 16.0;
+        for member in group {
+            let height = 30.0 + 16.0 * member.details.len() as f32;
+            if y - height < MARGIN {
+                pages.push(content);
+                content = Content::new();
+                y = A4.1 - MARGIN - 20.0;
+                text(
+                    &mut content,
+                    BOLD,
+                    20.0,
+                    MARGIN,
+                    y,
+                    &format!("Group {} (continued)", index + 1),
+                );
+                y -= 12.0;
+            }
+            y -= 30.0;
+            text(&mut content, BOLD, 20.0, MARGIN, y, &member.name);
+            for detail in &member.details {
+                y -= 16.0;
+                text(&mut content, REGULAR, 12.0, MARGIN + 12.0, y, detail);
+            }
+        }
+        pages.push(content);
+    }
+    return pages;
+}
+
+/// One page per member, folded along the dashed line. The upper half is printed upside down,
+/// so the name can be read from both sides of the table.
+fn name_tents(groups: &[Vec<Member>], title: &str) -> Vec<Content> {
+    let mut pages = Vec::new();
+    let middle = A4.1 / 2.0;
+    for (index, group) in groups.iter().enumerate() {
+        for member in group {
+            let subtitle = match title {
+                "" => format!("Group {}", index + 1),
+                _ => format!("{} - Group {}", title, index + 1),
+            };
+            let name_size = fit_size(&member.name, 48.0, A4.0 - 2.0 * MARGIN);
+            let mut content = Content::new();
+            content.set_line_width(0.5);
+            content.set_dash_pattern([4.0, 4.0], 0.0);
+            content.move_to(0.0, middle);
+            content.line_to(A4.0, middle);
+            content.stroke();
+            for upside_down in [false, true] {
+                // both halves are laid out like the lower one, the upper one rotated around the page center
+                let place = |x: f32, y: f32| match upside_down {
+                    false => (x, y),
+                    true => (A4.0 - x, A4.1 - y),
+                };
+                let name_width = text_width(&member.name, name_size);
+                let (x, y) = place((A4.0 - name_width) / 2.0, middle - 120.0);
+                rotated_text(
+                    &mut content,
+                    BOLD,
+                    name_size,
+                    x,
+                    y,
+                    upside_down,
+                    &member.name,
+                );
+                let subtitle_width = text_width(&subtitle, 16.0);
+                let (x, y) = place((A4.0 - subtitle_width) / 2.0, middle - 160.0);
+                rotated_text(&mut content, REGULAR, 16.0, x, y, upside_down, &subtitle);
+            }
+            pages.push(content);
+        }
+    }
+    return pages;
+}
+
+/// All groups as a list, groups start on the next page if they don't fit and continue there if they
+/// don't fit on a page at all.
+fn overview(groups: &[Vec<Member>], title: &str) -> Vec<Content> {
+    let mut pages = Vec::new();
+    let mut content = Content::new();
+    let mut y = A4.1 - MARGIN;
+    if !title.is_empty() {
+        y -= 20.0;
+        text(&mut content, BOLD, 20.0, MARGIN, y, title);
+        y -= 12.0;
+    }
+    for (index, group) in groups.iter().enumerate() {
+        let height = 30.0 + 16.0 * group.len() as f32;
+        if y - height < MARGIN && y < A4.1 - MARGIN - 40.0 {
+            pages.push(content);
+            content = Content::new();
+            y = A4.1 - MARGIN;
+        }
+        y -= 28.0;
+        text(
+            &mut content,
+            BOLD,
+            14.0,
+            MARGIN,
+            y,
+            &format!("Group {}", index + 1),
+        );
+        for member in group {
+            if y - 16.0 < MARGIN {
+                pages.push(content);
+                content = Content::new();
+                y = A4.1 - MARGIN - 28.0;
+                text(
+                    &mut content,
+                    BOLD,
+                    14.0,
+                    MARGIN,
+                    y,
+                    &format!("Group {} (continued)", index + 1),
+                );
+            }
+            y -= 16.0;
+            let line = match member.details.is_empty() {
+                true => member.name.clone(),
+                false => format!("{} ({})", member.name, member.details.join(", ")),
+            };
+            text(&mut content, REGULAR, 11.0, MARGIN + 12.0, y, &line);
+        }
+    }
+    pages.push(content);
+    return pages;
+}
+
+fn text(content: &mut Content, font: Name, size: f32, x: f32, y: f32, text: &str) {
+    rotated_text(content, font, size, x, y, false, text);
+}
+
+fn rotated_text(
+    content: &mut Content,
+    font: Name,
+    size: f32,
+    x: f32,
+    y: f32,
+    upside_down: bool,
+    text: &str,
+) {
+    let direction = if upside_down { -1.0 } else { 1.0 };
+    content.begin_text();
+    content.set_font(font, size);
+    content.set_text_matrix([direction, 0.0, 0.0, direction, x, y]);
+    content.show(Str(&win_ansi(text)));
+    content.end_text();
+}
+
+/// Largest font size up to the given one at which the text fits the width.
+fn fit_size(text: &str, size: f32, width: f32) -> f32 {
+    let natural = text_width(text, size);
+    return if natural > width {
+        size * width / natural
+    } else {
+        size
+    };
+}
+
+/// Width of the text in Helvetica, characters outside of ASCII are counted like digits.
+fn text_width(text: &str, size: f32) -> f32 {
+    const WIDTHS: [u16; 95] = [
+        278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556,
+        556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722,
+        722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722,
+        667, 944, 667, 667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556,
+        556, 222, 222, 500, 222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500,
+        500, 334, 260, 334, 584,
+    ];
+    let units: u32 = text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' => WIDTHS[c as usize - 32] as u32,
+            _ => 556,
+        })
+        .sum();
+    return units as f32 * size / 1000.0;
+}
+
+/// Encodes text for the standard fonts, characters they can't show are replaced by "?".
+fn win_ansi(text: &str) -> Vec<u8> {
+    return text
+        .chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{a0}'..='\u{ff}' => c as u8,
+            '€' => 0x80,
+            '‚' => 0x82,
+            '„' => 0x84,
+            '…' => 0x85,
+            '‘' => 0x91,
+            '’' => 0x92,
+            '“' => 0x93,
+            '”' => 0x94,
+            '–' => 0x96,
+            '—' => 0x97,
+            _ => b'?',
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::data::RawValue;
+    use indexmap::IndexMap;
+
+    fn long_group(members: usize) -> (MatchingResult, MatchingData) {
+        let mut data = MatchingData::new();
+        data.fields.insert("name".to_string(), "Name".to_string());
+        let mut group = Vec::new();
+        for index in 0..members {
+            let id = format!("{:03}", index);
+            let mut element = IndexMap::new();
+            element.insert(
+                "name".to_string(),
+                RawValue::Text(format!("Person {}", index)),
+            );
+            data.elements.insert(id.clone(), element);
+            group.push(id);
+        }
+        let result = MatchingResult {
+            score: 0,
+            connections: vec![group],
+            groups: Vec::new(),
+            metrics: Vec::new(),
+            violations: Vec::new(),
+        };
+        return (result, data);
+    }
+
+    fn page_count(pdf: &[u8]) -> usize {
+        let text = String::from_utf8_lossy(pdf);
+        return text.matches("/Type /Page").count() - text.matches("/Type /Pages").count();
+    }
+
+    #[test]
+    fn large_groups_continue_on_the_next_page() {
+        let (result, data) = long_group(100);
+        for layout in [PdfLayout::GroupPages, PdfLayout::Overview] {
+            let options = PdfOptions {
+                layout,
+                ..PdfOptions::default()
+            };
+            let pdf = write_pdf(&result, &data, &options);
+            assert!(page_count(&pdf) > 1, "{}", layout);
+            assert!(String::from_utf8_lossy(&pdf).contains("Group 1 (continued)"));
+            assert!(String::from_utf8_lossy(&pdf).contains("Person 99"));
+        }
+    }
+
+    #[test]
+    fn small_groups_fit_on_one_overview_page() {
+        let (result, data) = long_group(5);
+        let options = PdfOptions {
+            layout: PdfLayout::Overview,
+            ..PdfOptions::default()
+        };
+        let pdf = write_pdf(&result, &data, &options);
+        assert_eq!(page_count(&pdf), 1);
+        assert!(!String::from_utf8_lossy(&pdf).contains("(continued)"));
+    }
+
+    #[test]
+    fn name_tents_print_every_name_on_both_halves() {
+        let (result, data) = long_group(3);
+        let options = PdfOptions {
+            layout: PdfLayout::NameTents,
+            title: "Summer camp".to_string(),
+            ..PdfOptions::default()
+        };
+        let pdf = write_pdf(&result, &data, &options);
+        assert_eq!(page_count(&pdf), 3);
+        let text = String::from_utf8_lossy(&pdf);
+        assert_eq!(text.matches("(Person 2)").count(), 2);
+        assert_eq!(text.matches("(Summer camp - Group 1)").count(), 6);
+    }
+
+    #[test]
+    fn long_names_are_shrunk_to_fit() {
+        assert_eq!(fit_size("Tim", 48.0, 400.0), 48.0);
+        let name = "Maximilian Alexander von Hohenzollern-Sigmaringen";
+        let size = fit_size(name, 48.0, 400.0);
+        assert!(size < 48.0);
+        assert!((text_width(name, size) - 400.0).abs() < 0.01);
+    }
+}
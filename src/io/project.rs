@@ -0,0 +1,245 @@
+use crate::io::migration::{migrate, stamp};
+use crate::matching::data::MatchingData;
+use crate::matching::history::RunRecord;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+
+/// Text representation of a whole project, keys are written in the order of the project so saved files diff well.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ProjectFormat {
+    Json,
+    Yaml,
+    Toml,
+}
+
+impl ProjectFormat {
+    pub fn values() -> Vec<Self> {
+        return vec![
+            ProjectFormat::Json,
+            ProjectFormat::Yaml,
+            ProjectFormat::Toml,
+        ];
+    }
+
+    pub fn extension(&self) -> &'static str {
+        return match self {
+            ProjectFormat::Json => "json",
+            ProjectFormat::Yaml => "yaml",
+            ProjectFormat::Toml => "toml",
+        };
+    }
+
+    pub fn mime(&self) -> &'static str {
+        return match self {
+            ProjectFormat::Json => "application/json",
+            ProjectFormat::Yaml => "application/yaml",
+            ProjectFormat::Toml => "application/toml",
+        };
+    }
+}
+
+impl fmt::Display for ProjectFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Guesses the format from the first line that is neither empty nor a comment.
+pub fn detect_format(text: &str) -> ProjectFormat {
+    let line = text
+        .lines()
+        .map(|line| line.trim())
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .unwrap_or("");
+    if line.starts_with('{') {
+        return ProjectFormat::Json;
+    }
+    let is_table = line.starts_with('[') && line.ends_with(']');
+    let is_assignment = match (line.find('='), line.find(':')) {
+        (Some(equals), Some(colon)) => equals < colon,
+        (Some(_), None) => true,
+        _ => false,
+    };
+    if is_table || is_assignment {
+        return ProjectFormat::Toml;
+    }
+    return ProjectFormat::Yaml;
+}
+
+/// Everything saved in a project file, besides the matching data all parts are optional.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Project {
+    pub data: MatchingData,
+    pub history: Vec<RunRecord>, // results in the order they were computed
+    pub metadata: Option<Metadata>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Metadata {
+    pub saved: String, // ISO 8601 timestamp
+    pub app_version: String,
+}
+
+impl Metadata {
+    pub fn new(saved: String) -> Self {
+        Self {
+            saved,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+        }
+    }
+}
+
+/// All formats are read into a JSON value first, so map keys like output sizes are parsed the same way.
+/// Older versions are migrated, the notices describe what was changed.
+pub fn read_project(
+    text: &str,
+    format: Option<ProjectFormat>,
+) -> Result<(Project, Vec<String>), String> {
+    let format = format.unwrap_or_else(|| detect_format(text));
+    let value: Value = match format {
+        ProjectFormat::Json => serde_json::from_str(text).map_err(|error| error.to_string()),
+        ProjectFormat::Yaml => serde_yaml::from_str(text).map_err(|error| error.to_string()),
+        ProjectFormat::Toml => toml::from_str(text).map_err(|error| error.to_string()),
+    }
+    .map_err(|error| format!("{} is not well-formatted: {}", format, error))?;
+    let mut document = match value {
+        Value::Object(document) => document,
+        _ => return Err(format!("{} does not describe a project", format)),
+    };
+    let notices = migrate(&mut document)?;
+    document.remove("version");
+    let history = match document.remove("history") {
+        Some(history) => serde_json::from_value(history)
+            .map_err(|error| format!("The result history is invalid: {}", error))?,
+        None => Vec::new(),
+    };
+    let metadata = match document.remove("metadata") {
+        Some(metadata) => Some(
+            serde_json::from_value(metadata)
+                .map_err(|error| format!("The metadata is invalid: {}", error))?,
+        ),
+        None => None,
+    };
+    let data = serde_json::from_value(Value::Object(document))
+        .map_err(|error| format!("{} does not describe a project: {}", format, error))?;
+    return Ok((
+        Project {
+            data,
+            history,
+            metadata,
+        },
+        notices,
+    ));
+}
+
+pub fn write_project(project: &Project, format: ProjectFormat) -> Result<String, String> {
+    let to_value =
+        |value: Result<Value, serde_json::Error>| value.map_err(|error| error.to_string());
+    let mut document = match to_value(serde_json::to_value(&project.data))? {
+        Value::Object(document) => document,
+        _ => return Err("Project could not be written".to_string()),
+    };
+    stamp(&mut document);
+    if let Some(metadata) = &project.metadata {
+        document.insert(
+            "metadata".to_string(),
+            to_value(serde_json::to_value(metadata))?,
+        );
+    }
+    if !project.history.is_empty() {
+        document.insert(
+            "history".to_string(),
+            to_value(serde_json::to_value(&project.history))?,
+        );
+    }
+    let value = Value::Object(document);
+    return match format {
+        ProjectFormat::Json => {
+            serde_json::to_string_pretty(&value).map_err(|error| error.to_string())
+        }
+        ProjectFormat::Yaml => serde_yaml::to_string(&value).map_err(|error| error.to_string()),
+        ProjectFormat::Toml => toml::to_string_pretty(&value).map_err(|error| error.to_string()),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::history::RunKind;
+    use crate::matching::SolverSettings;
+
+    fn project() -> Project {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let created = "2024-05-01T12:00:00Z".to_string();
+        let history = vec![
+            RunRecord::run(
+                &data,
+                RunKind::Process,
+                &SolverSettings::default(),
+                created.clone(),
+            ),
+            RunRecord::run(
+                &data,
+                RunKind::Process,
+                &SolverSettings { max_steps: 1 },
+                created.clone(),
+            ),
+            RunRecord::run(
+                &data,
+                RunKind::Verify(vec![
+                    vec!["01".to_string(), "02".to_string()],
+                    vec!["03".to_string(), "06".to_string()],
+                    vec!["04".to_string(), "05".to_string()],
+                ]),
+                &SolverSettings::default(),
+                created.clone(),
+            ),
+        ];
+        return Project {
+            data,
+            history,
+            metadata: Some(Metadata::new(created)),
+        };
+    }
+
+    #[test]
+    fn projects_round_trip_in_every_format() {
+        let project = project();
+        assert!(project.history[0].outcome.is_ok());
+        assert!(project.history[1].outcome.is_err());
+        for format in ProjectFormat::values() {
+            let text = write_project(&project, format).unwrap();
+            assert_eq!(detect_format(&text), format);
+            let (read, notices) = read_project(&text, None).unwrap();
+            assert_eq!(read, project, "{}", format);
+            assert!(notices.is_empty());
+        }
+    }
+
+    #[test]
+    fn formats_are_detected_from_the_first_line() {
+        assert_eq!(detect_format("\n  {\"fields\": {}}"), ProjectFormat::Json);
+        assert_eq!(
+            detect_format("# comment\nversion = 1\n"),
+            ProjectFormat::Toml
+        );
+        assert_eq!(
+            detect_format("[fields]\ngroup1 = \"Name\""),
+            ProjectFormat::Toml
+        );
+        assert_eq!(
+            detect_format("fields:\n  group1: Name"),
+            ProjectFormat::Yaml
+        );
+        assert_eq!(detect_format("title: \"a = b\""), ProjectFormat::Yaml);
+    }
+
+    #[test]
+    fn files_that_are_no_projects_are_rejected() {
+        assert!(read_project("[1, 2]", Some(ProjectFormat::Json)).is_err());
+        assert!(read_project("{\"fields\": ", Some(ProjectFormat::Json)).is_err());
+        assert!(read_project("fields: {}\nhistory: 3\n", None).is_err());
+    }
+}
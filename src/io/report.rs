@@ -0,0 +1,232 @@
+use crate::matching::data::{MatchingData, MatchingResult};
+use minijinja::{AutoEscape, Environment};
+use serde_json::{json, Map, Value};
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ReportFormat {
+    Markdown,
+    Html, // values are escaped
+    Text,
+}
+
+impl ReportFormat {
+    pub fn values() -> Vec<Self> {
+        return vec![
+            ReportFormat::Markdown,
+            ReportFormat::Html,
+            ReportFormat::Text,
+        ];
+    }
+
+    pub fn extension(&self) -> &'static str {
+        return match self {
+            ReportFormat::Markdown => "md",
+            ReportFormat::Html => "html",
+            ReportFormat::Text => "txt",
+        };
+    }
+
+    pub fn mime(&self) -> &'static str {
+        return match self {
+            ReportFormat::Markdown => "text/markdown;charset=utf-8",
+            ReportFormat::Html => "text/html;charset=utf-8",
+            ReportFormat::Text => "text/plain;charset=utf-8",
+        };
+    }
+}
+
+impl fmt::Display for ReportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum BuiltinTemplate {
+    Announcement, // one section per group listing its members
+    Participants, // one message per element naming its group mates
+}
+
+impl BuiltinTemplate {
+    pub fn values() -> Vec<Self> {
+        return vec![BuiltinTemplate::Announcement, BuiltinTemplate::Participants];
+    }
+
+    pub fn source(&self, format: ReportFormat) -> &'static str {
+        return match (self, format) {
+            (BuiltinTemplate::Announcement, ReportFormat::Markdown) => {
+                include_str!("../../res/templates/announcement.md.j2")
+            }
+            (BuiltinTemplate::Announcement, ReportFormat::Html) => {
+                include_str!("../../res/templates/announcement.html.j2")
+            }
+            (BuiltinTemplate::Announcement, ReportFormat::Text) => {
+                include_str!("../../res/templates/announcement.txt.j2")
+            }
+            (BuiltinTemplate::Participants, ReportFormat::Markdown) => {
+                include_str!("../../res/templates/participants.md.j2")
+            }
+            (BuiltinTemplate::Participants, ReportFormat::Html) => {
+                include_str!("../../res/templates/participants.html.j2")
+            }
+            (BuiltinTemplate::Participants, ReportFormat::Text) => {
+                include_str!("../../res/templates/participants.txt.j2")
+            }
+        };
+    }
+}
+
+impl fmt::Display for BuiltinTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+pub fn render_report(
+    template: &str,
+    format: ReportFormat,
+    number: usize,
+    result: &MatchingResult,
+    data: &MatchingData,
+) -> Result<String, String> {
+    let mut environment = Environment::new();
+    environment.set_auto_escape_callback(move |_| match format {
+        ReportFormat::Html => AutoEscape::Html,
+        _ => AutoEscape::None,
+    });
+    return environment
+        .render_str(template, report_context(number, result, data))
+        .map_err(|error| format!("Template could not be rendered: {:#}", error));
+}
+
+/// Values available in templates: `result`, `fields`, `groups` with their `members`, and `participants`
+/// which are all members with their `group` number and their group `mates`.
+pub fn report_context(number: usize, result: &MatchingResult, data: &MatchingData) -> Value {
+    let member = |id: &String| {
+        let element = data.elements.get(id);
+        let value_of = |field: &String| {
+            element
+                .and_then(|element| element.get(field))
+                .map(|value| value.to_string())
+                .unwrap_or_default()
+        };
+        let mut fields = Map::new();
+        let mut values = Map::new();
+        for (field, name) in &data.fields {
+            fields.insert(name.clone(), Value::from(value_of(field)));
+            values.insert(field.clone(), Value::from(value_of(field)));
+        }
+        let name = match data.name_field() {
+            Some(field) if element.is_some_and(|element| element.contains_key(field)) => {
+                value_of(field)
+            }
+            _ => id.clone(),
+        };
+        return json!({ "id": id, "name": name, "fields": fields, "values": values });
+    };
+    let mut groups = Vec::new();
+    let mut participants = Vec::new();
+    for (index, group) in result.connections.iter().enumerate() {
+        let members: Vec<Value> = group.iter().map(&member).collect();
+        for (position, participant) in members.iter().enumerate() {
+            let mut participant = participant.clone();
+            participant["group"] = Value::from(index + 1);
+            participant["mates"] = members
+                .iter()
+                .enumerate()
+                .filter(|(other, _)| *other != position)
+                .map(|(_, mate)| mate.clone())
+                .collect();
+            participants.push(participant);
+        }
+        groups.push(json!({
+            "number": index + 1,
+            "score": result.groups.get(index).map(|group| group.score),
+            "members": members,
+        }));
+    }
+    return json!({
+        "result": { "number": number, "score": result.score },
+        "fields": data.fields.iter().map(|(id, name)| json!({ "id": id, "name": name })).collect::<Vec<Value>>(),
+        "groups": groups,
+        "participants": participants,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::data::RawValue;
+
+    fn grouping() -> (MatchingResult, MatchingData) {
+        let mut data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        data.elements["02"].insert("group1".to_string(), RawValue::Text("<Gabi>".to_string()));
+        let result = MatchingResult {
+            score: 3,
+            connections: vec![
+                vec!["01".to_string(), "02".to_string()],
+                vec!["04".to_string()],
+            ],
+            groups: Vec::new(),
+            metrics: Vec::new(),
+            violations: Vec::new(),
+        };
+        return (result, data);
+    }
+
+    #[test]
+    fn builtin_templates_render_in_every_format() {
+        let (result, data) = grouping();
+        for template in BuiltinTemplate::values() {
+            for format in ReportFormat::values() {
+                let report =
+                    render_report(template.source(format), format, 2, &result, &data).unwrap();
+                assert!(report.contains("Tim"), "{} {}", template, format);
+                assert!(report.contains("Mama"), "{} {}", template, format);
+            }
+        }
+    }
+
+    #[test]
+    fn groups_and_participants() {
+        let (result, data) = grouping();
+        let announcement = render_report(
+            BuiltinTemplate::Announcement.source(ReportFormat::Markdown),
+            ReportFormat::Markdown,
+            2,
+            &result,
+            &data,
+        )
+        .unwrap();
+        assert!(announcement.contains("# Groups of result 2"));
+        assert!(announcement.contains("## Group 1\n\n- Tim\n- <Gabi>"));
+        let participants = render_report(
+            BuiltinTemplate::Participants.source(ReportFormat::Text),
+            ReportFormat::Text,
+            2,
+            &result,
+            &data,
+        )
+        .unwrap();
+        assert!(participants.contains("Hello Tim, you are in group 1 together with <Gabi>."));
+        assert!(participants.contains("Hello Mama, you are in group 2."));
+    }
+
+    #[test]
+    fn html_values_are_escaped() {
+        let (result, data) = grouping();
+        let template =
+            "{% for p in participants %}{{ p.name }}|{{ p.fields.Standort }};{% endfor %}";
+        assert_eq!(
+            render_report(template, ReportFormat::Html, 1, &result, &data).unwrap(),
+            "Tim|Bonn;&lt;Gabi&gt;|Bonn;Mama|Aachen;"
+        );
+        assert_eq!(
+            render_report(template, ReportFormat::Markdown, 1, &result, &data).unwrap(),
+            "Tim|Bonn;<Gabi>|Bonn;Mama|Aachen;"
+        );
+        assert!(render_report("{{ unclosed", ReportFormat::Text, 1, &result, &data).is_err());
+    }
+}
@@ -0,0 +1,232 @@
+use crate::io::csv::Table;
+use crate::matching::layers::PairScore;
+use indexmap::IndexMap;
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ScoreFormat {
+    EdgeList, // one score per row: element, other element, score
+    Matrix, // element ids as first row and column, the cell of a row and column is the score between them
+}
+
+impl ScoreFormat {
+    pub fn values() -> Vec<Self> {
+        return vec![ScoreFormat::EdgeList, ScoreFormat::Matrix];
+    }
+}
+
+impl fmt::Display for ScoreFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScoreOptions {
+    pub format: Option<ScoreFormat>, // detected from the table if not set
+    pub symmetric: bool, // an edge list score also counts from the other element to the first
+    pub factor: f64, // scores are multiplied and rounded, e.g. to turn affinities from 0 to 1 into points
+}
+
+impl Default for ScoreOptions {
+    fn default() -> Self {
+        Self {
+            format: None,
+            symmetric: true,
+            factor: 1.0,
+        }
+    }
+}
+
+/// Scores read from a table, scores of unknown elements are left out.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ScoreImport {
+    pub scores: Vec<PairScore>,
+    pub unknown: Vec<String>, // ids not found in the elements, in the order they occur
+}
+
+/// A matrix has element ids as headers after the first one, an empty top left cell or more than three columns,
+/// anything else is an edge list.
+pub fn detect_score_format<VALUE>(
+    table: &Table,
+    elements: &IndexMap<String, VALUE>,
+) -> ScoreFormat {
+    let id_headers = table.headers.len() > 1
+        && table.headers[1..]
+            .iter()
+            .all(|cell| elements.contains_key(cell));
+    let corner_empty = table.headers.first().is_some_and(|cell| cell.is_empty());
+    if id_headers || corner_empty || table.headers.len() > 3 {
+        return ScoreFormat::Matrix;
+    }
+    return ScoreFormat::EdgeList;
+}
+
+/// Matrices need their header row, edge lists may have one. Empty cells and scores of zero are skipped.
+pub fn read_scores<VALUE>(
+    table: &Table,
+    elements: &IndexMap<String, VALUE>,
+    options: &ScoreOptions,
+) -> Result<ScoreImport, String> {
+    let mut import = ScoreImport::default();
+    let mut cells: Vec<(usize, &String, &String, &String)> = Vec::new(); // row number, from, to, score
+    match options
+        .format
+        .unwrap_or_else(|| detect_score_format(table, elements))
+    {
+        ScoreFormat::EdgeList => {
+            if table.headers.len() < 3 {
+                return Err(
+                    "An edge list needs three columns: element, other element and score"
+                        .to_string(),
+                );
+            }
+            for (index, row) in table.rows.iter().enumerate() {
+                cells.push((index + 1, &row[0], &row[1], &row[2]));
+                if options.symmetric {
+                    cells.push((index + 1, &row[1], &row[0], &row[2]));
+                }
+            }
+        }
+        ScoreFormat::Matrix => {
+            for (index, row) in table.rows.iter().enumerate() {
+                for (to, score) in table.headers.iter().zip(row.iter()).skip(1) {
+                    cells.push((index + 1, &row[0], to, score));
+                }
+            }
+        }
+    }
+    for (row, from, to, score) in cells {
+        if score.is_empty() || from == to {
+            continue;
+        }
+        let value = score
+            .replace(',', ".")
+            .parse::<f64>()
+            .map_err(|_| format!("Row {}: {} is not a number", row, score))?;
+        let points = (value * options.factor)
+            .round()
+            .clamp(i16::MIN as f64 + 1.0, i16::MAX as f64) as i16;
+        let mut known = true;
+        for id in [from, to] {
+            if !elements.contains_key(id) {
+                known = false;
+                if !import.unknown.contains(id) {
+                    import.unknown.push(id.clone());
+                }
+            }
+        }
+        if known && points != 0 {
+            import.scores.push(PairScore {
+                from: from.clone(),
+                to: to.clone(),
+                score: points,
+            });
+        }
+    }
+    return Ok(import);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn table(rows: &[&[&str]]) -> Table {
+        return Table::from_rows(
+            rows.iter()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect(),
+            true,
+        );
+    }
+
+    fn elements() -> IndexMap<String, ()> {
+        return ["01", "02", "03"]
+            .iter()
+            .map(|id| (id.to_string(), ()))
+            .collect();
+    }
+
+    fn score(from: &str, to: &str, score: i16) -> PairScore {
+        return PairScore {
+            from: from.to_string(),
+            to: to.to_string(),
+            score,
+        };
+    }
+
+    #[test]
+    fn matrices_are_detected_by_their_headers() {
+        let elements = elements();
+        let ids = table(&[&["", "01", "02"], &["01", "", "1"]]);
+        assert_eq!(detect_score_format(&ids, &elements), ScoreFormat::Matrix);
+        let named = table(&[&["Id", "01", "02"], &["01", "", "1"]]);
+        assert_eq!(detect_score_format(&named, &elements), ScoreFormat::Matrix);
+        let corner = table(&[&["", "A", "B"], &["01", "", "1"]]);
+        assert_eq!(detect_score_format(&corner, &elements), ScoreFormat::Matrix);
+        let wide = table(&[&["Id", "A", "B", "C"], &["01", "", "1", "2"]]);
+        assert_eq!(detect_score_format(&wide, &elements), ScoreFormat::Matrix);
+        let edges = table(&[&["From", "To", "Score"], &["01", "02", "3"]]);
+        assert_eq!(
+            detect_score_format(&edges, &elements),
+            ScoreFormat::EdgeList
+        );
+    }
+
+    #[test]
+    fn matrix_cells_are_scores_from_the_row_to_the_column() {
+        let matrix = table(&[
+            &["", "01", "02", "03"],
+            &["01", "5", "2", ""],
+            &["02", "0", "", "-1"],
+        ]);
+        let import = read_scores(&matrix, &elements(), &ScoreOptions::default()).unwrap();
+        assert_eq!(
+            import.scores,
+            vec![score("01", "02", 2), score("02", "03", -1)]
+        );
+        assert!(import.unknown.is_empty());
+    }
+
+    #[test]
+    fn edge_lists_are_scaled_and_mirrored() {
+        let edges = table(&[
+            &["From", "To", "Affinity"],
+            &["01", "02", "0,5"],
+            &["02", "09", "1"],
+            &["08", "03", "1"],
+        ]);
+        let options = ScoreOptions {
+            factor: 10.0,
+            ..ScoreOptions::default()
+        };
+        let import = read_scores(&edges, &elements(), &options).unwrap();
+        assert_eq!(
+            import.scores,
+            vec![score("01", "02", 5), score("02", "01", 5)]
+        );
+        assert_eq!(import.unknown, vec!["09", "08"]);
+
+        let one_way = ScoreOptions {
+            symmetric: false,
+            ..options
+        };
+        let import = read_scores(&edges, &elements(), &one_way).unwrap();
+        assert_eq!(import.scores, vec![score("01", "02", 5)]);
+    }
+
+    #[test]
+    fn invalid_scores_are_rejected() {
+        let edges = table(&[&["From", "To", "Score"], &["01", "02", "many"]]);
+        assert_eq!(
+            read_scores(&edges, &elements(), &ScoreOptions::default()),
+            Err("Row 1: many is not a number".to_string())
+        );
+        let options = ScoreOptions {
+            format: Some(ScoreFormat::EdgeList),
+            ..ScoreOptions::default()
+        };
+        let pairs = table(&[&["From", "To"], &["01", "02"]]);
+        assert!(read_scores(&pairs, &elements(), &options).is_err());
+    }
+}
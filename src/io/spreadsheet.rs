@@ -0,0 +1,300 @@
+use crate::io::csv::{result_rows, ExportOptions, Table};
+use crate::io::xml_escape;
+use crate::matching::data::{MatchingData, MatchingResult};
+use crate::matching::parser::print_rule;
+use crate::matching::score::Scorer;
+use calamine::{open_workbook_auto_from_rs, Reader};
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use std::fmt;
+use std::io::{Cursor, Write};
+use zip::result::ZipResult;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, DateTime, ZipWriter};
+
+pub const XLSX_MIME: &str = "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet";
+pub const ODS_MIME: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+/// Whether a file name looks like a workbook (.xlsx, .xlsm, .xls or .ods) instead of CSV text.
+pub fn is_spreadsheet(name: &str) -> bool {
+    let name = name.to_lowercase();
+    return [".xlsx", ".xlsm", ".xlsb", ".xls", ".ods"]
+        .iter()
+        .any(|extension| name.ends_with(extension));
+}
+
+pub fn sheet_names(bytes: &[u8]) -> Result<Vec<String>, String> {
+    let workbook = open_workbook_auto_from_rs(Cursor::new(bytes))
+        .map_err(|error| format!("Spreadsheet could not be opened: {}", error))?;
+    return Ok(workbook.sheet_names());
+}
+
+/// Reads the cells of a sheet, the first one if none is given.
+pub fn read_sheet(bytes: &[u8], sheet: Option<&str>, has_headers: bool) -> Result<Table, String> {
+    let mut workbook = open_workbook_auto_from_rs(Cursor::new(bytes))
+        .map_err(|error| format!("Spreadsheet could not be opened: {}", error))?;
+    let name = match sheet {
+        Some(sheet) => sheet.to_string(),
+        None => workbook
+            .sheet_names()
+            .first()
+            .cloned()
+            .ok_or("Spreadsheet has no sheets")?,
+    };
+    let range = workbook
+        .worksheet_range(&name)
+        .map_err(|error| format!("Sheet {} could not be read: {}", name, error))?;
+    let rows = range
+        .rows()
+        .map(|row| {
+            row.iter()
+                .map(|cell| cell.to_string().trim().to_string())
+                .collect()
+        })
+        .collect();
+    return Ok(Table::from_rows(rows, has_headers));
+}
+
+/// File format of exported workbooks.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum SpreadsheetFormat {
+    Xlsx, // Excel
+    Ods,  // OpenDocument, e.g. LibreOffice
+}
+
+impl SpreadsheetFormat {
+    pub fn values() -> Vec<Self> {
+        return vec![SpreadsheetFormat::Xlsx, SpreadsheetFormat::Ods];
+    }
+
+    pub fn extension(&self) -> &'static str {
+        return match self {
+            SpreadsheetFormat::Xlsx => "xlsx",
+            SpreadsheetFormat::Ods => "ods",
+        };
+    }
+
+    pub fn mime(&self) -> &'static str {
+        return match self {
+            SpreadsheetFormat::Xlsx => XLSX_MIME,
+            SpreadsheetFormat::Ods => ODS_MIME,
+        };
+    }
+}
+
+impl fmt::Display for SpreadsheetFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+/// Workbook with one sheet per result, laid out like the CSV export, and a sheet of the rules used.
+pub fn write_workbook(
+    results: &[(usize, MatchingResult)],
+    data: &MatchingData,
+    options: &ExportOptions,
+    format: SpreadsheetFormat,
+) -> Result<Vec<u8>, String> {
+    let sheets = sheets(results, data, options);
+    return match format {
+        SpreadsheetFormat::Xlsx => build_xlsx(&sheets).map_err(|error| error.to_string()),
+        SpreadsheetFormat::Ods => build_ods(&sheets).map_err(|error| error.to_string()),
+    };
+}
+
+/// Name and rows of every sheet, the first row holds the headers.
+fn sheets(
+    results: &[(usize, MatchingResult)],
+    data: &MatchingData,
+    options: &ExportOptions,
+) -> Vec<(String, Vec<Vec<String>>)> {
+    let mut sheets: Vec<(String, Vec<Vec<String>>)> = results
+        .iter()
+        .map(|(number, result)| {
+            (
+                format!("Result {}", number),
+                result_rows(result, data, options),
+            )
+        })
+        .collect();
+    let mut rows = vec![vec![
+        "Rule".to_string(),
+        "Severity".to_string(),
+        "Score".to_string(),
+    ]];
+    for rule in &data.rules {
+        rows.push(vec![
+            print_rule(rule, &data.fields),
+            rule.severity.to_string(),
+            rule.get_score().to_string(),
+        ]);
+    }
+    sheets.push(("Rules".to_string(), rows));
+    return sheets;
+}
+
+/// Cells holding plain integers are written as numbers, so ids like "01" stay text.
+fn as_number(cell: &str) -> Option<i64> {
+    return match cell.parse::<i64>() {
+        Ok(number) if number.to_string() == cell => Some(number),
+        _ => None,
+    };
+}
+
+fn build_xlsx(sheets: &Vec<(String, Vec<Vec<String>>)>) -> Result<Vec<u8>, XlsxError> {
+    let bold = Format::new().set_bold();
+    let mut workbook = Workbook::new();
+    for (name, rows) in sheets {
+        let worksheet = workbook.add_worksheet();
+        worksheet.set_name(name)?;
+        write_rows(worksheet, rows, &bold)?;
+    }
+    return workbook.save_to_buffer();
+}
+
+fn write_rows(
+    worksheet: &mut Worksheet,
+    rows: &[Vec<String>],
+    header_format: &Format,
+) -> Result<(), XlsxError> {
+    for (row_index, row) in rows.iter().enumerate() {
+        for (column, cell) in row.iter().enumerate() {
+            let (row_index, column) = (row_index as u32, column as u16);
+            if row_index == 0 {
+                worksheet.write_string_with_format(row_index, column, cell, header_format)?;
+                continue;
+            }
+            match as_number(cell) {
+                Some(number) => {
+                    worksheet.write_number(row_index, column, number as f64)?;
+                }
+                None => {
+                    worksheet.write_string(row_index, column, cell)?;
+                }
+            }
+        }
+    }
+    worksheet.autofit();
+    return Ok(());
+}
+
+/// The smallest valid OpenDocument spreadsheet: the uncompressed mimetype first, a manifest and the content.
+fn build_ods(sheets: &Vec<(String, Vec<Vec<String>>)>) -> ZipResult<Vec<u8>> {
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Stored)
+        .last_modified_time(DateTime::default());
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    zip.start_file("mimetype", options)?;
+    zip.write_all(ODS_MIME.as_bytes())?;
+    zip.start_file("META-INF/manifest.xml", options)?;
+    zip.write_all(ODS_MANIFEST.as_bytes())?;
+    zip.start_file("content.xml", options)?;
+    zip.write_all(ods_content(sheets).as_bytes())?;
+    return Ok(zip.finish()?.into_inner());
+}
+
+fn ods_content(sheets: &Vec<(String, Vec<Vec<String>>)>) -> String {
+    let mut xml = String::from(concat!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+        "<office:document-content",
+        " xmlns:office=\"urn:oasis:names:tc:opendocument:xmlns:office:1.0\"",
+        " xmlns:style=\"urn:oasis:names:tc:opendocument:xmlns:style:1.0\"",
+        " xmlns:table=\"urn:oasis:names:tc:opendocument:xmlns:table:1.0\"",
+        " xmlns:text=\"urn:oasis:names:tc:opendocument:xmlns:text:1.0\"",
+        " xmlns:fo=\"urn:oasis:names:tc:opendocument:xmlns:xsl-fo-compatible:1.0\"",
+        " office:version=\"1.2\">\n",
+        "<office:automatic-styles><style:style style:name=\"header\" style:family=\"table-cell\">",
+        "<style:text-properties fo:font-weight=\"bold\"/></style:style></office:automatic-styles>\n",
+        "<office:body><office:spreadsheet>\n",
+    ));
+    for (name, rows) in sheets {
+        xml += &format!("<table:table table:name=\"{}\">\n", xml_escape(name));
+        for (row_index, row) in rows.iter().enumerate() {
+            xml += "<table:table-row>";
+            for cell in row {
+                let text = format!("<text:p>{}</text:p>", xml_escape(cell));
+                xml += &match as_number(cell) {
+                    _ if row_index == 0 => format!(
+                        "<table:table-cell table:style-name=\"header\" office:value-type=\"string\">{}</table:table-cell>",
+                        text
+                    ),
+                    Some(number) => format!(
+                        "<table:table-cell office:value-type=\"float\" office:value=\"{}\">{}</table:table-cell>",
+                        number, text
+                    ),
+                    None => format!(
+                        "<table:table-cell office:value-type=\"string\">{}</table:table-cell>",
+                        text
+                    ),
+                };
+            }
+            xml += "</table:table-row>\n";
+        }
+        xml += "</table:table>\n";
+    }
+    xml += "</office:spreadsheet></office:body></office:document-content>\n";
+    return xml;
+}
+
+const ODS_MANIFEST: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+    "<manifest:manifest xmlns:manifest=\"urn:oasis:names:tc:opendocument:xmlns:manifest:1.0\" manifest:version=\"1.2\">\n",
+    " <manifest:file-entry manifest:full-path=\"/\" manifest:version=\"1.2\" manifest:media-type=\"application/vnd.oasis.opendocument.spreadsheet\"/>\n",
+    " <manifest:file-entry manifest:full-path=\"content.xml\" manifest:media-type=\"text/xml\"/>\n",
+    "</manifest:manifest>\n",
+);
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn export(format: SpreadsheetFormat) -> Vec<u8> {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let result = MatchingResult {
+            score: 3,
+            connections: vec![
+                vec!["01".to_string(), "02".to_string()],
+                vec!["04".to_string()],
+            ],
+            groups: Vec::new(),
+            metrics: Vec::new(),
+            violations: Vec::new(),
+        };
+        let options = ExportOptions {
+            layout: crate::io::csv::ExportLayout::Long,
+            fields: vec!["group1".to_string()],
+            ..ExportOptions::default()
+        };
+        return write_workbook(&[(2, result)], &data, &options, format).unwrap();
+    }
+
+    #[test]
+    fn spreadsheet_file_names() {
+        assert!(is_spreadsheet("Teams.XLSX"));
+        assert!(is_spreadsheet("teams.ods"));
+        assert!(!is_spreadsheet("teams.csv"));
+    }
+
+    #[test]
+    fn workbooks_are_read_back() {
+        for format in SpreadsheetFormat::values() {
+            let bytes = export(format);
+            assert_eq!(sheet_names(&bytes).unwrap(), vec!["Result 2", "Rules"]);
+            let table = read_sheet(&bytes, None, true).unwrap();
+            assert_eq!(table.headers, vec!["Group", "Id", "Name"]);
+            assert_eq!(
+                table.rows,
+                vec![
+                    vec!["1", "01", "Tim"],
+                    vec!["1", "02", "Gabi"],
+                    vec!["2", "04", "Mama"],
+                ],
+                "{}",
+                format
+            );
+            let rules = read_sheet(&bytes, Some("Rules"), true).unwrap();
+            assert_eq!(rules.headers, vec!["Rule", "Severity", "Score"]);
+            assert_eq!(rules.rows.len(), 4);
+        }
+    }
+}
@@ -6,6 +6,7 @@ extern crate yew;
 
 use log::Level;
 
+mod io;
 mod matching;
 mod ui;
 
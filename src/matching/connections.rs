@@ -1,12 +1,17 @@
-use crate::matching::rules::{Rule, RuleActions, RuleSeverity};
-use crate::matching::score::Scorer;
+use crate::matching::layers::ScoreLayer;
+use crate::matching::pairs::PairRule;
+use crate::matching::rules::{Comparing, Rule, RuleActions, RuleOperand, RuleSeverity};
+use crate::matching::schema::ElementValues;
+use crate::matching::score::{GroupScore, ScoreEntry, ScoreSource, Scorer};
+use debug_print::debug_println;
 use std::collections::HashMap;
 use std::fmt;
-use std::iter::FromIterator;
 
 pub(crate) struct Connections {
     matrix: Vec<Vec<i16>>,
     fixed_order: Vec<String>,
+    required: Vec<(usize, usize)>, // pairs that have to end up in the same group
+    contributions: HashMap<(usize, usize), Vec<(ScoreSource, i16)>>, // cell -> points added by rules and pairs
 }
 
 impl Connections {
@@ -20,21 +25,112 @@ impl Connections {
             .expect("No item with that index!")
             .to_string();
     }
+    pub(crate) fn score(&self, x: usize, y: usize) -> i16 {
+        return self.matrix[x][y];
+    }
+    pub(crate) fn required(&self) -> &Vec<(usize, usize)> {
+        return &self.required;
+    }
+    pub(crate) fn index_of(&self, id: &str) -> Option<usize> {
+        return self.fixed_order.iter().position(|element| element == id);
+    }
+
+    fn contribute(&mut self, x: usize, y: usize, source: ScoreSource, points: i16) {
+        if points != 0 {
+            self.contributions
+                .entry((x, y))
+                .or_default()
+                .push((source, points));
+        }
+    }
+
+    /// Explains the score of a group by listing the points every source added to its connections.
+    pub(crate) fn breakdown(&self, group: &Vec<usize>) -> GroupScore {
+        let mut breakdown = GroupScore::default();
+        for x in group {
+            for y in group {
+                if x == y || self.matrix[*x][*y] == i16::MIN {
+                    continue; // excluded connections only occur in imported groupings and are reported there
+                }
+                let mut sources =
+                    vec![(ScoreSource::Base, RuleSeverity::Standard.get_score() as i16)];
+                if let Some(contributions) = self.contributions.get(&(*x, *y)) {
+                    sources.extend(contributions.iter().cloned());
+                }
+                for (source, points) in sources {
+                    breakdown.entries.push(ScoreEntry {
+                        source,
+                        element: self.for_index(x),
+                        target: self.for_index(y),
+                        points: points as i32,
+                    });
+                }
+                breakdown.score += self.matrix[*x][*y] as i32;
+            }
+        }
+        return breakdown;
+    }
+
+    pub(crate) fn apply_pair(&mut self, index: usize, pair: &PairRule) {
+        let (x, y) = match (self.index_of(&pair.first), self.index_of(&pair.second)) {
+            (Some(x), Some(y)) if x != y => (x, y),
+            _ => {
+                debug_println!("skipping pair {} - {}", pair.first, pair.second);
+                return;
+            }
+        };
+        match pair.severity {
+            RuleSeverity::Force => self.required.push((x, y)),
+            RuleSeverity::ForceExclude => {
+                self.matrix[x][y] = i16::MIN;
+                self.matrix[y][x] = i16::MIN;
+            }
+            _ => {
+                for (from, to) in [(x, y), (y, x)] {
+                    let before = self.matrix[from][to];
+                    if before != i16::MIN {
+                        self.matrix[from][to] =
+                            before.saturating_add(pair.get_score()).max(i16::MIN + 1);
+                        let points = self.matrix[from][to].saturating_sub(before);
+                        self.contribute(from, to, ScoreSource::Pair(index), points);
+                    }
+                }
+            }
+        }
+    }
+
+    /// Adds the imported scores, connections that can't be made stay excluded.
+    pub(crate) fn apply_layer(&mut self, index: usize, layer: &ScoreLayer) {
+        for entry in &layer.scores {
+            let (x, y) = match (self.index_of(&entry.from), self.index_of(&entry.to)) {
+                (Some(x), Some(y)) if x != y => (x, y),
+                _ => {
+                    debug_println!("skipping score {} - {}", entry.from, entry.to);
+                    continue;
+                }
+            };
+            let before = self.matrix[x][y];
+            if before != i16::MIN {
+                self.matrix[x][y] = before.saturating_add(entry.score).max(i16::MIN + 1);
+                let points = self.matrix[x][y].saturating_sub(before);
+                self.contribute(x, y, ScoreSource::Layer(index), points);
+            }
+        }
+    }
 }
 
 pub(crate) trait Connector<S, T> {
     fn from_data(data: &T) -> Self;
-    fn apply(&mut self, to_apply: &S, extra_info: &T);
+    fn apply(&mut self, index: usize, to_apply: &S, extra_info: &T);
     fn calc_score(&self, group: &Vec<&usize>) -> (bool, i32);
     fn possible_connections(&self) -> Vec<Vec<usize>>;
     //fn find_highest_score(&self, possible_connections: &Vec<Vec<i16>>) -> (i64, Vec<Vec<i16>>);
 }
 
-impl Connector<Rule, HashMap<String, HashMap<String, String>>> for Connections {
-    fn from_data(data: &HashMap<String, HashMap<String, String>>) -> Connections {
-        let mut fixed_order = Vec::from_iter(data.keys().cloned());
-        fixed_order.sort();
-        let size = data.len();
+impl Connector<Rule, ElementValues> for Connections {
+    fn from_data(data: &ElementValues) -> Connections {
+        let fixed_order = data.ids();
+        let size = fixed_order.len();
         let mut matrix = vec![vec![RuleSeverity::Standard.get_score() as i16; size]; size];
         for index in 0..size {
             matrix[index][index] = i16::MIN;
@@ -43,13 +139,29 @@ impl Connector<Rule, HashMap<String, HashMap<String, String>>> for Connections {
         return Connections {
             matrix,
             fixed_order,
+            required: Vec::new(),
+            contributions: HashMap::new(),
         };
     }
 
-    fn apply(&mut self, rule: &Rule, fields: &HashMap<String, HashMap<String, String>>) {
+    fn apply(&mut self, index: usize, rule: &Rule, fields: &ElementValues) {
         let size = self.fixed_order.len();
+        let direct = rule.operand == RuleOperand::References
+            && rule.severity != RuleSeverity::Force
+            && !rule.negated
+            && !rule.is_literal();
         for x in 0..size {
-            for y in 0..size {
+            let targets: Vec<usize> = if direct {
+                // only the referenced elements can match, so write their cells directly
+                fields
+                    .references(&self.fixed_order[x], &rule.field)
+                    .iter()
+                    .filter_map(|id| self.index_of(id))
+                    .collect()
+            } else {
+                (0..size).collect()
+            };
+            for y in targets {
                 match rule.severity {
                     RuleSeverity::Force | RuleSeverity::ForceExclude => {}
                     _ => {
@@ -58,19 +170,28 @@ impl Connector<Rule, HashMap<String, HashMap<String, String>>> for Connections {
                         }
                     }
                 }
-                rule.check_and_apply(
-                    &*self.fixed_order[x],
-                    &*self.fixed_order[y],
+                if !rule.in_scope(&self.fixed_order[x], &self.fixed_order[y], fields) {
+                    continue;
+                }
+                let points = rule.check_and_apply(
+                    &self.fixed_order[x],
+                    &self.fixed_order[y],
                     fields,
                     x,
                     y,
                     &mut self.matrix,
                 );
+                self.contribute(x, y, ScoreSource::Rule(index), points);
             }
         }
     }
 
     fn calc_score(&self, group: &Vec<&usize>) -> (bool, i32) {
+        for (x, y) in &self.required {
+            if group.contains(&x) != group.contains(&y) {
+                return (false, i32::MIN);
+            }
+        }
         let mut score: i32 = 0;
         let size = group.len();
         for x in 0..size {
@@ -1,16 +1,35 @@
 extern crate serde;
 extern crate serde_json;
 
+use crate::matching::grouping::Violation;
+use crate::matching::layers::ScoreLayer;
+use crate::matching::metrics::ElementMetrics;
+use crate::matching::pairs::PairRule;
 use crate::matching::rules::Rule;
-use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use crate::matching::schema::FieldType;
+use crate::matching::score::GroupScore;
+use indexmap::IndexMap;
+use serde::de::{self, MapAccess, SeqAccess, Visitor};
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+use std::fmt;
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct MatchingData {
-    pub(crate) fields: HashMap<String, String>, // id -> field name/text
-    pub(crate) elements: HashMap<String, HashMap<String, String>>, // id -> { fieldId -> value }
+    pub(crate) fields: IndexMap<String, String>, // id -> field name/text, in column order
+    #[serde(deserialize_with = "deserialize_elements")]
+    pub(crate) elements: IndexMap<String, IndexMap<String, RawValue>>, // id -> { fieldId -> value }, in row order
     pub(crate) rules: Vec<Rule>,
-    pub(crate) outputs: HashMap<usize, i16>, // possible output sizes: size -> max usages
+    pub(crate) outputs: BTreeMap<usize, i16>, // possible output sizes: size -> max usages
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) pairs: Vec<PairRule>, // constraints between specific elements
+    #[serde(default, skip_serializing_if = "IndexMap::is_empty")]
+    pub(crate) schema: IndexMap<String, FieldType>, // field id -> type, fields without type are text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) layers: Vec<ScoreLayer>, // imported scores between elements
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) name_field: Option<String>, // field holding the names of the elements, see `name_field()`
 }
 
 impl Default for MatchingData {
@@ -20,6 +39,10 @@ impl Default for MatchingData {
             elements: Default::default(),
             rules: vec![],
             outputs: Default::default(),
+            pairs: vec![],
+            schema: Default::default(),
+            layers: vec![],
+            name_field: None,
         }
     }
 }
@@ -27,16 +50,314 @@ impl Default for MatchingData {
 impl MatchingData {
     pub fn new() -> Self {
         Self {
-            fields: HashMap::new(),
-            elements: HashMap::new(),
+            fields: IndexMap::new(),
+            elements: IndexMap::new(),
             rules: Vec::new(),
-            outputs: HashMap::new(),
+            outputs: BTreeMap::new(),
+            pairs: Vec::new(),
+            schema: IndexMap::new(),
+            layers: Vec::new(),
+            name_field: None,
         }
     }
+
+    pub fn field_type(&self, field: &str) -> FieldType {
+        return *self.schema.get(field).unwrap_or(&FieldType::Text);
+    }
+
+    /// Field holding the names of the elements, used for labels and to resolve references.
+    /// Data without a chosen name field uses the first column, until the columns are reordered.
+    pub fn name_field(&self) -> Option<&String> {
+        return match &self.name_field {
+            Some(field) if self.fields.contains_key(field) => Some(field),
+            _ => self.fields.keys().next(),
+        };
+    }
+
+    /// Id of the element followed by its name, e.g. "01 Tim".
+    pub fn element_label(&self, id: &str) -> String {
+        return match self
+            .name_field()
+            .and_then(|field| self.elements.get(id)?.get(field))
+        {
+            Some(value) => format!("{} {}", id, value),
+            None => id.to_string(),
+        };
+    }
+
+    /// Moves a field column by the given number of places, clamped to the first and last column.
+    /// The name field is kept, so references resolve to the same elements as before.
+    pub fn move_field(&mut self, id: &str, offset: isize) {
+        self.name_field = self.name_field().cloned();
+        move_entry(&mut self.fields, id, offset);
+    }
+
+    /// Moves an element row by the given number of places, clamped to the first and last row.
+    pub fn move_element(&mut self, id: &str, offset: isize) {
+        move_entry(&mut self.elements, id, offset);
+    }
+}
+
+fn move_entry<V>(map: &mut IndexMap<String, V>, key: &str, offset: isize) {
+    if let Some(index) = map.get_index_of(key) {
+        let target = (index as isize + offset).clamp(0, map.len() as isize - 1) as usize;
+        map.move_index(index, target);
+    }
+}
+
+/// Elements are written as map by id, but can also be read from an array of objects with an "id" key.
+/// Values are checked one by one, so errors name the element and field, missing values (null) are skipped.
+fn deserialize_elements<'de, D>(
+    deserializer: D,
+) -> Result<IndexMap<String, IndexMap<String, RawValue>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let entries: Vec<(String, Value)> = match Value::deserialize(deserializer)? {
+        Value::Object(elements) => elements.into_iter().collect(),
+        Value::Array(list) => {
+            let mut entries = Vec::new();
+            for (index, element) in list.into_iter().enumerate() {
+                let fields = match element {
+                    Value::Object(fields) => fields,
+                    _ => {
+                        return Err(de::Error::custom(format!(
+                            "element {} has to be an object of field values",
+                            index + 1
+                        )))
+                    }
+                };
+                let id = match fields.get("id") {
+                    Some(id) => match RawValue::deserialize(id.clone()) {
+                        Ok(RawValue::Text(id)) if !id.trim().is_empty() => id.trim().to_string(),
+                        _ => {
+                            return Err(de::Error::custom(format!(
+                                "element {} has an invalid id",
+                                index + 1
+                            )))
+                        }
+                    },
+                    None => {
+                        return Err(de::Error::custom(format!(
+                            "element {} has no id",
+                            index + 1
+                        )))
+                    }
+                };
+                let fields = fields.into_iter().filter(|(key, _)| key != "id");
+                entries.push((id, Value::Object(fields.collect())));
+            }
+            entries
+        }
+        _ => {
+            return Err(de::Error::custom(
+                "elements have to be a map by id or a list of objects",
+            ))
+        }
+    };
+    let mut elements = IndexMap::new();
+    for (id, element) in entries {
+        let fields = match element {
+            Value::Object(fields) => fields,
+            _ => {
+                return Err(de::Error::custom(format!(
+                    "element {} has to be an object of field values",
+                    id
+                )))
+            }
+        };
+        let mut values = IndexMap::new();
+        for (field, value) in fields {
+            if value.is_null() {
+                continue;
+            }
+            let value = RawValue::deserialize(value).map_err(|error| {
+                de::Error::custom(format!("element {}, field {}: {}", id, field, error))
+            })?;
+            values.insert(field, value);
+        }
+        if elements.insert(id.clone(), values).is_some() {
+            return Err(de::Error::custom(format!(
+                "the id {} is used more than once",
+                id
+            )));
+        }
+    }
+    return Ok(elements);
+}
+
+/// Value of an element as stored, either a single string or a list of strings (JSON array).
+/// Numbers, booleans and dates are read as their text, e.g. from hand-written YAML or TOML.
+#[derive(Serialize, Clone, Debug, PartialEq)]
+#[serde(untagged)]
+pub enum RawValue {
+    Text(String),
+    List(Vec<String>),
 }
 
-#[derive(Serialize, Deserialize)]
+impl<'de> Deserialize<'de> for RawValue {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        return deserializer.deserialize_any(RawValueVisitor);
+    }
+}
+
+struct RawValueVisitor;
+
+impl<'de> Visitor<'de> for RawValueVisitor {
+    type Value = RawValue;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a text, number, boolean, date or a list of them")
+    }
+
+    fn visit_str<E: de::Error>(self, text: &str) -> Result<RawValue, E> {
+        return Ok(RawValue::Text(text.to_string()));
+    }
+
+    fn visit_string<E: de::Error>(self, text: String) -> Result<RawValue, E> {
+        return Ok(RawValue::Text(text));
+    }
+
+    fn visit_bool<E: de::Error>(self, value: bool) -> Result<RawValue, E> {
+        return Ok(RawValue::Text(value.to_string()));
+    }
+
+    fn visit_i64<E: de::Error>(self, value: i64) -> Result<RawValue, E> {
+        return Ok(RawValue::Text(value.to_string()));
+    }
+
+    fn visit_u64<E: de::Error>(self, value: u64) -> Result<RawValue, E> {
+        return Ok(RawValue::Text(value.to_string()));
+    }
+
+    fn visit_f64<E: de::Error>(self, value: f64) -> Result<RawValue, E> {
+        return Ok(RawValue::Text(value.to_string()));
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<RawValue, A::Error> {
+        let mut items = Vec::new();
+        while let Some(item) = seq.next_element::<RawValue>()? {
+            match item {
+                RawValue::Text(text) => items.push(text),
+                RawValue::List(_) => return Err(de::Error::custom("lists can't be nested")),
+            }
+        }
+        return Ok(RawValue::List(items));
+    }
+
+    /// TOML dates and times arrive as a map with a single private key.
+    fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<RawValue, A::Error> {
+        if let Some(key) = map.next_key::<String>()? {
+            if key == TOML_DATETIME {
+                let text = map.next_value::<String>()?;
+                if map.next_key::<String>()?.is_none() {
+                    return Ok(RawValue::Text(text));
+                }
+            }
+        }
+        return Err(de::Error::invalid_type(de::Unexpected::Map, &self));
+    }
+}
+
+const TOML_DATETIME: &str = "$__toml_private_datetime";
+
+impl fmt::Display for RawValue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RawValue::Text(text) => write!(f, "{}", text),
+            RawValue::List(items) => write!(f, "{}", items.join(", ")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
 pub struct MatchingResult {
     pub score: i32,
     pub connections: Vec<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<GroupScore>, // score breakdown per group, same order as the connections
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub metrics: Vec<ElementMetrics>, // satisfaction per element, sorted by element id
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub violations: Vec<Violation>, // only imported groupings can violate constraints
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn name_field_stays_when_fields_are_moved() {
+        let mut data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        assert_eq!(data.name_field().map(String::as_str), Some("group1"));
+        data.move_field("group2", -1);
+        assert_eq!(
+            data.fields.keys().next().map(String::as_str),
+            Some("group2")
+        );
+        assert_eq!(data.name_field().map(String::as_str), Some("group1"));
+
+        data.name_field = Some("group5".to_string());
+        assert_eq!(data.name_field().map(String::as_str), Some("group5"));
+        data.name_field = Some("unknown".to_string());
+        assert_eq!(data.name_field().map(String::as_str), Some("group2"));
+    }
+
+    fn ids<V>(map: &IndexMap<String, V>) -> Vec<&str> {
+        return map.keys().map(String::as_str).collect();
+    }
+
+    #[test]
+    fn rows_keep_the_order_of_the_file_and_can_be_moved() {
+        let mut data: MatchingData = serde_json::from_str(
+            r#"{"fields": {"b": "B", "a": "A"},
+                "elements": {"2": {"a": "x"}, "10": {"a": "y"}, "1": {"a": "z"}},
+                "rules": [], "outputs": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(ids(&data.fields), vec!["b", "a"]);
+        assert_eq!(ids(&data.elements), vec!["2", "10", "1"]);
+        data.move_element("1", -5);
+        assert_eq!(ids(&data.elements), vec!["1", "2", "10"]);
+        data.move_element("2", 1);
+        assert_eq!(ids(&data.elements), vec!["1", "10", "2"]);
+        data.move_element("unknown", 1);
+        assert_eq!(ids(&data.elements), vec!["1", "10", "2"]);
+
+        let written = serde_json::to_string(&data).unwrap();
+        let read: MatchingData = serde_json::from_str(&written).unwrap();
+        assert_eq!(ids(&read.elements), vec!["1", "10", "2"]);
+    }
+
+    #[test]
+    fn elements_are_read_from_a_list() {
+        let data: MatchingData = serde_json::from_str(
+            r#"{"fields": {"a": "A"},
+                "elements": [{"id": " 7 ", "a": "x"}, {"id": 3, "a": null}],
+                "rules": [], "outputs": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(ids(&data.elements), vec!["7", "3"]);
+        assert_eq!(
+            data.elements["7"].get("a"),
+            Some(&RawValue::Text("x".to_string()))
+        );
+        assert!(data.elements["3"].is_empty());
+
+        for elements in [r#"[{"a": "x"}]"#, r#"[{"id": ""}]"#, r#"["x"]"#, "3"] {
+            let text = format!(
+                r#"{{"fields": {{}}, "elements": {}, "rules": [], "outputs": {{}}}}"#,
+                elements
+            );
+            assert!(
+                serde_json::from_str::<MatchingData>(&text).is_err(),
+                "{}",
+                elements
+            );
+        }
+    }
 }
@@ -0,0 +1,310 @@
+use crate::matching::data::{MatchingData, MatchingResult};
+use crate::matching::parser::print_rule;
+use crate::matching::score::ScoreSource;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// Content of the rules and pairs a result was computed with, as their indices change when they are edited.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct Sources {
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rules: Vec<String>, // printed rule per index
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pairs: Vec<String>, // printed pair per index
+}
+
+impl Sources {
+    pub fn of(matching_data: &MatchingData) -> Self {
+        Self {
+            rules: matching_data
+                .rules
+                .iter()
+                .map(|rule| print_rule(rule, &matching_data.fields))
+                .collect(),
+            pairs: matching_data
+                .pairs
+                .iter()
+                .map(|pair| match pair.weight {
+                    Some(weight) => format!(
+                        "Pair {} - {}: {} {}",
+                        pair.first, pair.second, pair.severity, weight
+                    ),
+                    None => format!("Pair {} - {}: {}", pair.first, pair.second, pair.severity),
+                })
+                .collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        return self.rules.is_empty() && self.pairs.is_empty();
+    }
+
+    /// Content of a rule or pair, results stored without it fall back to the index.
+    fn key(&self, source: &ScoreSource) -> String {
+        let content = match source {
+            ScoreSource::Rule(index) => self.rules.get(*index),
+            ScoreSource::Pair(index) => self.pairs.get(*index),
+            _ => None,
+        };
+        return match content {
+            Some(content) => content.clone(),
+            None => format!("{:?}", source),
+        };
+    }
+
+    fn keys(&self) -> BTreeSet<&String> {
+        return self.rules.iter().chain(self.pairs.iter()).collect();
+    }
+}
+
+/// Element that ended up with different group members in the second result.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Move {
+    pub element: String,
+    pub before: Vec<String>, // other members of the group in the first result
+    pub after: Vec<String>,  // other members of the group in the second result
+}
+
+/// Wish of an element that is fulfilled in one result but not in the other.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WishChange {
+    pub element: String,
+    pub source: String,  // content of the rule or pair
+    pub satisfied: bool, // state in the second result
+}
+
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct ResultDiff {
+    pub score_delta: i32,
+    pub moved: Vec<Move>,
+    pub wishes: Vec<WishChange>,
+    pub totals: BTreeMap<String, (usize, usize)>, // satisfied wishes per rule or pair, before and after
+    pub only_before: Vec<String>, // rules and pairs only the first result was computed with
+    pub only_after: Vec<String>,  // rules and pairs only the second result was computed with
+}
+
+/// Compares two results, rules and pairs are matched by their content.
+/// Wishes of rules and pairs that only exist for one of the results are left out.
+pub fn diff(
+    before: &MatchingResult,
+    before_sources: &Sources,
+    after: &MatchingResult,
+    after_sources: &Sources,
+) -> ResultDiff {
+    let keys_before = before_sources.keys();
+    let keys_after = after_sources.keys();
+    let mut result_diff = ResultDiff {
+        score_delta: after.score - before.score,
+        moved: moved(before, after),
+        only_before: keys_before
+            .difference(&keys_after)
+            .map(|key| (*key).clone())
+            .collect(),
+        only_after: keys_after
+            .difference(&keys_before)
+            .map(|key| (*key).clone())
+            .collect(),
+        ..ResultDiff::default()
+    };
+    let one_sided: BTreeSet<&String> = result_diff
+        .only_before
+        .iter()
+        .chain(result_diff.only_after.iter())
+        .collect();
+    let wishes_before = wishes(before, before_sources);
+    let wishes_after = wishes(after, after_sources);
+    for ((element, source), satisfied) in &wishes_after {
+        if let Some(satisfied_before) = wishes_before.get(&(element.clone(), source.clone())) {
+            if satisfied_before != satisfied {
+                result_diff.wishes.push(WishChange {
+                    element: element.clone(),
+                    source: source.clone(),
+                    satisfied: *satisfied,
+                });
+            }
+        }
+    }
+    result_diff
+        .wishes
+        .sort_by(|a, b| (&a.source, &a.element).cmp(&(&b.source, &b.element)));
+    for ((_, source), satisfied) in &wishes_before {
+        if !one_sided.contains(source) {
+            result_diff.totals.entry(source.clone()).or_default().0 += *satisfied as usize;
+        }
+    }
+    for ((_, source), satisfied) in &wishes_after {
+        if !one_sided.contains(source) {
+            result_diff.totals.entry(source.clone()).or_default().1 += *satisfied as usize;
+        }
+    }
+    return result_diff;
+}
+
+/// Elements that got different group members in the second result, sorted by element id.
+pub fn moved(before: &MatchingResult, after: &MatchingResult) -> Vec<Move> {
+    let mates_before = mates(before);
+    let mates_after = mates(after);
+    let mut elements: Vec<&String> = mates_before.keys().chain(mates_after.keys()).collect();
+    elements.sort();
+    elements.dedup();
+    let mut moved = Vec::new();
+    for element in elements {
+        let before = mates_before.get(element).cloned().unwrap_or_default();
+        let after = mates_after.get(element).cloned().unwrap_or_default();
+        if before != after {
+            moved.push(Move {
+                element: element.clone(),
+                before,
+                after,
+            });
+        }
+    }
+    return moved;
+}
+
+/// Other members of the group of every element, sorted.
+fn mates(result: &MatchingResult) -> HashMap<String, Vec<String>> {
+    let mut mates = HashMap::new();
+    for group in &result.connections {
+        for element in group {
+            let mut others: Vec<String> = group
+                .iter()
+                .filter(|other| *other != element)
+                .cloned()
+                .collect();
+            others.sort();
+            mates.insert(element.clone(), others);
+        }
+    }
+    return mates;
+}
+
+fn wishes(result: &MatchingResult, sources: &Sources) -> HashMap<(String, String), bool> {
+    return result
+        .metrics
+        .iter()
+        .flat_map(|metric| {
+            metric.wishes.iter().map(move |wish| {
+                (
+                    (metric.element.clone(), sources.key(&wish.source)),
+                    wish.satisfied,
+                )
+            })
+        })
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::metrics::{ElementMetrics, Wish};
+
+    fn result(connections: &[&[&str]], wishes: &[(ScoreSource, bool)]) -> MatchingResult {
+        return MatchingResult {
+            score: 0,
+            connections: connections
+                .iter()
+                .map(|group| group.iter().map(|id| id.to_string()).collect())
+                .collect(),
+            groups: Vec::new(),
+            metrics: vec![ElementMetrics {
+                element: "01".to_string(),
+                group: 0,
+                received: 0,
+                wishes: wishes
+                    .iter()
+                    .map(|(source, satisfied)| Wish {
+                        source: *source,
+                        satisfied: *satisfied,
+                    })
+                    .collect(),
+                rank: 1,
+            }],
+            violations: Vec::new(),
+        };
+    }
+
+    fn sources(rules: &[&str]) -> Sources {
+        return Sources {
+            rules: rules.iter().map(|rule| rule.to_string()).collect(),
+            pairs: Vec::new(),
+        };
+    }
+
+    #[test]
+    fn moved_elements() {
+        let before = result(&[&["01", "02"], &["03"]], &[]);
+        let after = result(&[&["01", "03"], &["02"]], &[]);
+        let moved = moved(&before, &after);
+        assert_eq!(
+            moved
+                .iter()
+                .map(|m| m.element.as_str())
+                .collect::<Vec<&str>>(),
+            vec!["01", "02", "03"]
+        );
+        assert_eq!(moved[0].before, vec!["02".to_string()]);
+        assert_eq!(moved[0].after, vec!["03".to_string()]);
+    }
+
+    #[test]
+    fn rules_are_matched_by_content() {
+        let before = result(
+            &[&["01"]],
+            &[(ScoreSource::Rule(0), true), (ScoreSource::Rule(1), false)],
+        );
+        // a rule was inserted in front, so the indices of the others shifted
+        let after = result(
+            &[&["01"]],
+            &[
+                (ScoreSource::Rule(0), true),
+                (ScoreSource::Rule(1), true),
+                (ScoreSource::Rule(2), true),
+            ],
+        );
+        let result_diff = diff(
+            &before,
+            &sources(&["prefer: same(a)", "prefer: same(b)"]),
+            &after,
+            &sources(&["force: same(c)", "prefer: same(a)", "prefer: same(b)"]),
+        );
+        assert_eq!(
+            result_diff.wishes,
+            vec![WishChange {
+                element: "01".to_string(),
+                source: "prefer: same(b)".to_string(),
+                satisfied: true,
+            }]
+        );
+        assert_eq!(
+            result_diff.totals,
+            BTreeMap::from([
+                ("prefer: same(a)".to_string(), (1, 1)),
+                ("prefer: same(b)".to_string(), (0, 1)),
+            ])
+        );
+        assert!(result_diff.only_before.is_empty());
+        assert_eq!(result_diff.only_after, vec!["force: same(c)".to_string()]);
+    }
+
+    #[test]
+    fn results_without_sources_are_matched_by_index() {
+        let before = result(&[&["01"]], &[(ScoreSource::Rule(0), false)]);
+        let after = result(&[&["01"]], &[(ScoreSource::Rule(0), true)]);
+        let result_diff = diff(&before, &Sources::default(), &after, &Sources::default());
+        assert_eq!(result_diff.wishes.len(), 1);
+        assert_eq!(result_diff.totals.get("Rule(0)"), Some(&(0, 1)));
+    }
+
+    #[test]
+    fn sources_of_the_data() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let sources = Sources::of(&data);
+        assert_eq!(sources.rules.len(), data.rules.len());
+        assert_eq!(sources.pairs.len(), data.pairs.len());
+        for (rule, printed) in data.rules.iter().zip(&sources.rules) {
+            assert_eq!(printed, &print_rule(rule, &data.fields));
+        }
+    }
+}
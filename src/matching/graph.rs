@@ -0,0 +1,117 @@
+use crate::matching::connections::Connections;
+use crate::matching::rules::RuleSeverity;
+use crate::matching::score::Scorer;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq)]
+pub enum EdgeKind {
+    Required,  // forced pair, both elements have to share a group
+    Excluded,  // the elements can't share a group
+    Preferred, // more points than the standard score
+    Avoided,   // less points than the standard score
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct GraphEdge {
+    pub source: String, // element id
+    pub target: String, // element id
+    pub kind: EdgeKind,
+    pub score: Option<i16>, // points of the connection, none for required and excluded edges
+    pub mutual: bool,       // the connection from target to source is the same
+}
+
+/// Connections between the elements that differ from the standard score, as nodes and edges.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct PreferenceGraph {
+    pub nodes: Vec<String>, // element ids, sorted by id like in the solver
+    pub edges: Vec<GraphEdge>,
+}
+
+impl PreferenceGraph {
+    pub(crate) fn from_connections(connections: &Connections) -> Self {
+        let base = RuleSeverity::Standard.get_score() as i16;
+        let size = connections.len();
+        let mut edges = Vec::new();
+        for (x, y) in connections.required() {
+            edges.push(GraphEdge {
+                source: connections.for_index(x),
+                target: connections.for_index(y),
+                kind: EdgeKind::Required,
+                score: None,
+                mutual: true,
+            });
+        }
+        for x in 0..size {
+            for y in (x + 1)..size {
+                let (there, back) = (connections.score(x, y), connections.score(y, x));
+                let directions = if there == back {
+                    vec![(x, y, there, true)]
+                } else {
+                    vec![(x, y, there, false), (y, x, back, false)]
+                };
+                for (from, to, score, mutual) in directions {
+                    let (kind, score) = match score {
+                        i16::MIN => (EdgeKind::Excluded, None),
+                        score if score > base => (EdgeKind::Preferred, Some(score)),
+                        score if score < base => (EdgeKind::Avoided, Some(score)),
+                        _ => continue,
+                    };
+                    edges.push(GraphEdge {
+                        source: connections.for_index(&from),
+                        target: connections.for_index(&to),
+                        kind,
+                        score,
+                        mutual,
+                    });
+                }
+            }
+        }
+        return Self {
+            nodes: (0..size)
+                .map(|index| connections.for_index(&index))
+                .collect(),
+            edges,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::data::MatchingData;
+    use crate::matching::preference_graph;
+
+    fn edge(graph: &PreferenceGraph, source: &str, target: &str) -> Option<GraphEdge> {
+        return graph
+            .edges
+            .iter()
+            .find(|edge| edge.source == source && edge.target == target)
+            .cloned();
+    }
+
+    #[test]
+    fn edges_differ_from_the_standard_score() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let graph = preference_graph(&data).unwrap();
+        assert_eq!(graph.nodes, vec!["01", "02", "03", "04", "05", "06"]);
+        // different Standort
+        let excluded = edge(&graph, "01", "04").unwrap();
+        assert_eq!((excluded.kind, excluded.score), (EdgeKind::Excluded, None));
+        assert!(excluded.mutual);
+        // same Abteilung, but Thomas wishes Tim
+        let avoided = edge(&graph, "01", "06").unwrap();
+        assert_eq!((avoided.kind, avoided.score), (EdgeKind::Avoided, Some(-1)));
+        assert!(!avoided.mutual);
+        assert_eq!(edge(&graph, "06", "01"), None);
+        // same Abteilung, but Tim and Gabi wish each other
+        assert_eq!(edge(&graph, "01", "02"), None);
+    }
+}
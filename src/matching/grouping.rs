@@ -0,0 +1,231 @@
+use crate::matching::connections::{Connections, Connector};
+use crate::matching::data::{MatchingData, MatchingResult};
+use crate::matching::metrics::element_metrics;
+use crate::matching::schema::ElementValues;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
+
+/// Constraint broken by a grouping, groups are numbered from 1 like in the result table.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum Violation {
+    UnknownElement(String),
+    DuplicateElement(String),
+    MissingElement(String),
+    GroupTooSmall(usize),
+    Excluded(usize, String, String), // group, element, excluded element
+    RequiredSplit(String, String),
+    NoOutput(usize, usize), // group, size
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Violation::UnknownElement(id) => write!(f, "{} is not an element", id),
+            Violation::DuplicateElement(id) => write!(f, "{} is part of several groups", id),
+            Violation::MissingElement(id) => write!(f, "{} is not part of any group", id),
+            Violation::GroupTooSmall(group) => {
+                write!(f, "Group {} has less than 2 elements", group)
+            }
+            Violation::Excluded(group, id, target) => {
+                write!(f, "Group {}: {} excludes {}", group, id, target)
+            }
+            Violation::RequiredSplit(first, second) => {
+                write!(f, "{} and {} have to be in the same group", first, second)
+            }
+            Violation::NoOutput(group, size) => {
+                write!(f, "Group {}: no output left for {} elements", group, size)
+            }
+        }
+    }
+}
+
+/// Parses a grouping, either as JSON array of id arrays or as one comma separated group per line.
+pub fn parse_grouping(text: &str) -> Result<Vec<Vec<String>>, String> {
+    if text.trim_start().starts_with('[') {
+        return serde_json::from_str(text)
+            .map_err(|error| format!("Grouping is not well-formatted: {}", error));
+    }
+    return Ok(text
+        .lines()
+        .map(|line| {
+            line.split(',')
+                .map(|id| id.trim().to_string())
+                .filter(|id| !id.is_empty())
+                .collect::<Vec<String>>()
+        })
+        .filter(|group| !group.is_empty())
+        .collect());
+}
+
+pub(crate) fn verify(
+    data: &MatchingData,
+    values: &ElementValues,
+    connections: &Connections,
+    groups: &Vec<Vec<String>>,
+) -> MatchingResult {
+    let mut violations = Vec::new();
+    let mut seen = HashSet::new();
+    let mut indices: Vec<Vec<usize>> = Vec::new();
+    for group in groups {
+        let mut members = Vec::new();
+        for id in group {
+            match connections.index_of(id) {
+                None => violations.push(Violation::UnknownElement(id.clone())),
+                Some(_) if seen.contains(id) => {
+                    violations.push(Violation::DuplicateElement(id.clone()))
+                }
+                Some(index) => {
+                    seen.insert(id.clone());
+                    members.push(index);
+                }
+            }
+        }
+        indices.push(members);
+    }
+    for id in values.ids() {
+        if !seen.contains(&id) {
+            violations.push(Violation::MissingElement(id));
+        }
+    }
+    for (number, group) in indices
+        .iter()
+        .enumerate()
+        .map(|(index, group)| (index + 1, group))
+    {
+        if group.len() < 2 {
+            violations.push(Violation::GroupTooSmall(number));
+        }
+        if connections.calc_score(&group.iter().collect()).0 {
+            continue;
+        }
+        for x in group {
+            for y in group {
+                if x != y && connections.score(*x, *y) == i16::MIN {
+                    violations.push(Violation::Excluded(
+                        number,
+                        connections.for_index(x),
+                        connections.for_index(y),
+                    ));
+                }
+            }
+        }
+    }
+    for (x, y) in connections.required() {
+        let together = indices
+            .iter()
+            .any(|group| group.contains(x) && group.contains(y));
+        let placed = indices
+            .iter()
+            .flatten()
+            .any(|index| index == x || index == y);
+        if placed && !together {
+            violations.push(Violation::RequiredSplit(
+                connections.for_index(x),
+                connections.for_index(y),
+            ));
+        }
+    }
+    violations.extend(check_outputs(&data.outputs, &indices));
+    let breakdown: Vec<_> = indices
+        .iter()
+        .map(|group| connections.breakdown(group))
+        .collect();
+    return MatchingResult {
+        score: breakdown.iter().map(|group| group.score).sum(),
+        connections: indices
+            .iter()
+            .map(|group| {
+                group
+                    .iter()
+                    .map(|index| connections.for_index(index))
+                    .collect()
+            })
+            .collect(),
+        groups: breakdown,
+        metrics: element_metrics(data, values, connections, &indices),
+        violations,
+    };
+}
+
+/// Assigns the largest groups first to the smallest output that fits them, like the solver does.
+fn check_outputs(outputs: &BTreeMap<usize, i16>, groups: &[Vec<usize>]) -> Vec<Violation> {
+    let mut available = outputs.clone();
+    let mut order: Vec<usize> = (0..groups.len()).collect();
+    order.sort_by(|a, b| groups[*b].len().cmp(&groups[*a].len()));
+    let mut missing = Vec::new();
+    for index in order {
+        let size = groups[index].len();
+        let output = available
+            .iter()
+            .filter(|(output_size, amount)| **output_size >= size && **amount != 0)
+            .map(|(output_size, _)| *output_size)
+            .min();
+        match output {
+            Some(output_size) => {
+                let amount = available[&output_size];
+                if amount > 0 {
+                    available.insert(output_size, amount - 1);
+                }
+            }
+            None => missing.push((index + 1, size)),
+        }
+    }
+    missing.sort();
+    return missing
+        .into_iter()
+        .map(|(group, size)| Violation::NoOutput(group, size))
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::{process, verify_grouping};
+
+    fn concept() -> MatchingData {
+        return serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+    }
+
+    #[test]
+    fn groupings_are_read_from_lines_or_json() {
+        let expected = vec![
+            vec!["01".to_string(), "02".to_string()],
+            vec!["03".to_string()],
+        ];
+        assert_eq!(parse_grouping("01, 02\n\n 03 ,\n"), Ok(expected.clone()));
+        assert_eq!(parse_grouping(r#"[["01", "02"], ["03"]]"#), Ok(expected));
+        assert!(parse_grouping("[[\"01\"").is_err());
+    }
+
+    #[test]
+    fn processed_groupings_have_the_same_score() {
+        let data = concept();
+        let result = process(&data).unwrap();
+        let verified = verify_grouping(&data, &result.connections).unwrap();
+        assert!(verified.violations.is_empty());
+        assert_eq!(verified.score, result.score);
+    }
+
+    #[test]
+    fn violations_of_a_grouping() {
+        let grouping = parse_grouping("01, 04\n02, 02, 99\n03").unwrap();
+        let result = verify_grouping(&concept(), &grouping).unwrap();
+        assert_eq!(
+            result.violations,
+            vec![
+                Violation::DuplicateElement("02".to_string()),
+                Violation::UnknownElement("99".to_string()),
+                Violation::MissingElement("05".to_string()),
+                Violation::MissingElement("06".to_string()),
+                Violation::Excluded(1, "01".to_string(), "04".to_string()),
+                Violation::Excluded(1, "04".to_string(), "01".to_string()),
+                Violation::GroupTooSmall(2),
+                Violation::GroupTooSmall(3),
+            ]
+        );
+        assert_eq!(result.violations[4].to_string(), "Group 1: 01 excludes 04");
+        // the remaining members are scored anyway
+        assert_eq!(result.connections[1], vec!["02".to_string()]);
+    }
+}
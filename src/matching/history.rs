@@ -0,0 +1,170 @@
+use crate::matching::data::{MatchingData, MatchingResult};
+use crate::matching::diff::Sources;
+use crate::matching::{process_with_settings, verify_grouping, MatchingError, SolverSettings};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// How a result came about, so it can be computed again from the same input.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum RunKind {
+    Process,
+    Verify(Vec<Vec<String>>), // element ids per group of the imported grouping
+}
+
+/// A result together with everything needed to reproduce and audit it later.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct RunRecord {
+    pub created: String, // ISO 8601 timestamp
+    pub kind: RunKind,
+    pub settings: SolverSettings,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>, // the solver is deterministic, so no seed is used yet
+    pub input_hash: String, // of the matching data the result was computed from
+    #[serde(default, skip_serializing_if = "Sources::is_empty")]
+    pub sources: Sources, // to compare results by rule even if the rules were edited in between
+    pub outcome: Result<MatchingResult, MatchingError>,
+}
+
+impl RunRecord {
+    pub fn run(
+        matching_data: &MatchingData,
+        kind: RunKind,
+        settings: &SolverSettings,
+        created: String,
+    ) -> Self {
+        let outcome = match &kind {
+            RunKind::Process => process_with_settings(matching_data, settings),
+            RunKind::Verify(groups) => verify_grouping(matching_data, groups),
+        };
+        return Self {
+            created,
+            kind,
+            settings: settings.clone(),
+            seed: None,
+            input_hash: input_hash(matching_data),
+            sources: Sources::of(matching_data),
+            outcome,
+        };
+    }
+
+    /// Computes the result again, from the given data if it still has the recorded hash.
+    pub fn reproduce(&self, matching_data: &MatchingData, created: String) -> Result<Self, String> {
+        if !self.matches(matching_data) {
+            return Err("The data has changed since the result was computed".to_string());
+        }
+        return Ok(Self::run(
+            matching_data,
+            self.kind.clone(),
+            &self.settings,
+            created,
+        ));
+    }
+
+    pub fn matches(&self, matching_data: &MatchingData) -> bool {
+        return self.input_hash == input_hash(matching_data);
+    }
+}
+
+/// FNV-1a hash of the data serialized as JSON, stable across platforms and builds.
+/// Keys are sorted first, as the order of fields and elements doesn't change results by itself.
+/// The order only decides the name field if none is chosen, so the name field in use is hashed too.
+pub fn input_hash(matching_data: &MatchingData) -> String {
+    let mut value = serde_json::to_value(matching_data).unwrap_or_default();
+    if let Value::Object(map) = &mut value {
+        map.insert(
+            "name_field".to_string(),
+            Value::from(matching_data.name_field().cloned()),
+        );
+    }
+    let json = sorted_keys(value).to_string();
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for byte in json.bytes() {
+        hash ^= byte as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    return format!("{:016x}", hash);
+}
+
+fn sorted_keys(value: Value) -> Value {
+    return match value {
+        Value::Object(map) => {
+            let mut entries: Vec<(String, Value)> = map.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| (key, sorted_keys(value)))
+                    .collect(),
+            )
+        }
+        Value::Array(items) => Value::Array(items.into_iter().map(sorted_keys).collect()),
+        _ => value,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn input_hash_depends_on_the_name_field_but_not_the_column_order() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let hash = input_hash(&data);
+
+        let mut moved = data.clone();
+        moved.move_field("group2", -1);
+        assert_eq!(input_hash(&moved), hash);
+
+        let mut renamed = data.clone();
+        renamed.name_field = Some("group5".to_string());
+        assert_ne!(input_hash(&renamed), hash);
+    }
+
+    #[test]
+    fn runs_are_reproduced_from_unchanged_data() {
+        let mut data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let record = RunRecord::run(
+            &data,
+            RunKind::Process,
+            &SolverSettings::default(),
+            "2024-05-01T12:00:00Z".to_string(),
+        );
+        assert!(record.matches(&data));
+        assert_eq!(record.sources, Sources::of(&data));
+
+        let again = record
+            .reproduce(&data, "2024-05-02T12:00:00Z".to_string())
+            .unwrap();
+        assert_eq!(again.outcome, record.outcome);
+        assert_eq!(again.input_hash, record.input_hash);
+        assert_eq!(again.created, "2024-05-02T12:00:00Z");
+
+        data.rules.pop();
+        assert!(!record.matches(&data));
+        assert!(record
+            .reproduce(&data, "2024-05-03T12:00:00Z".to_string())
+            .is_err());
+    }
+
+    #[test]
+    fn verified_groupings_are_recorded() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let groups = vec![
+            vec!["01".to_string(), "02".to_string()],
+            vec!["03".to_string(), "06".to_string()],
+            vec!["04".to_string(), "05".to_string()],
+        ];
+        let record = RunRecord::run(
+            &data,
+            RunKind::Verify(groups.clone()),
+            &SolverSettings::default(),
+            "2024-05-01T12:00:00Z".to_string(),
+        );
+        assert_eq!(record.outcome, verify_grouping(&data, &groups));
+        let written = serde_json::to_string(&record).unwrap();
+        assert_eq!(serde_json::from_str::<RunRecord>(&written).unwrap(), record);
+    }
+}
@@ -0,0 +1,22 @@
+use serde::{Deserialize, Serialize};
+
+/// Points one element gives another, e.g. from a survey or counted past collaborations.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PairScore {
+    pub(crate) from: String, // element id
+    pub(crate) to: String,   // element id
+    pub(crate) score: i16,
+}
+
+/// Scores computed outside of the tool, added to the connections next to the points of rules and pairs.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScoreLayer {
+    pub(crate) name: String, // e.g. the name of the imported file
+    pub(crate) scores: Vec<PairScore>,
+}
+
+impl ScoreLayer {
+    pub fn new(name: String, scores: Vec<PairScore>) -> Self {
+        Self { name, scores }
+    }
+}
@@ -0,0 +1,218 @@
+use crate::matching::connections::Connections;
+use crate::matching::data::MatchingData;
+use crate::matching::rules::{RuleActions, RuleSeverity};
+use crate::matching::schema::ElementValues;
+use crate::matching::score::{ScoreSource, Scorer};
+use serde::{Deserialize, Serialize};
+
+/// Whether a rule or pair an element is involved in was fulfilled by its group.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Wish {
+    pub source: ScoreSource,
+    pub satisfied: bool,
+}
+
+/// How well a single element is served by the group it ended up in.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ElementMetrics {
+    pub element: String,
+    pub group: usize,      // index of the group in the result
+    pub received: i32,     // points the other members of the group contribute to the element
+    pub wishes: Vec<Wish>, // preferring and excluding rules or pairs involving the element
+    pub rank: usize,       // 1 if no other group of the result would give the element more points
+}
+
+impl ElementMetrics {
+    pub fn satisfied(&self) -> usize {
+        return self.wishes.iter().filter(|wish| wish.satisfied).count();
+    }
+
+    /// Share of satisfied wishes, elements without wishes count as fully satisfied.
+    pub fn satisfaction(&self) -> f64 {
+        if self.wishes.is_empty() {
+            return 1.0;
+        }
+        return self.satisfied() as f64 / self.wishes.len() as f64;
+    }
+}
+
+/// Computes the metrics of every element for the given groups of element indices.
+pub(crate) fn element_metrics(
+    data: &MatchingData,
+    values: &ElementValues,
+    connections: &Connections,
+    groups: &[Vec<usize>],
+) -> Vec<ElementMetrics> {
+    let mut metrics = Vec::new();
+    for (group_index, group) in groups.iter().enumerate() {
+        for element in group {
+            let received_by_group: Vec<i32> = groups
+                .iter()
+                .map(|other_group| received(connections, *element, other_group))
+                .collect();
+            let received = received_by_group[group_index];
+            metrics.push(ElementMetrics {
+                element: connections.for_index(element),
+                group: group_index,
+                received,
+                wishes: wishes(data, values, connections, *element, group),
+                rank: 1 + received_by_group
+                    .iter()
+                    .filter(|points| **points > received)
+                    .count(),
+            });
+        }
+    }
+    metrics.sort_by(|a, b| a.element.cmp(&b.element));
+    return metrics;
+}
+
+/// Points the members of a group would contribute to the element, excluded connections count as 0.
+fn received(connections: &Connections, element: usize, group: &[usize]) -> i32 {
+    return group
+        .iter()
+        .filter(|other| **other != element)
+        .map(|other| connections.score(*other, element))
+        .filter(|score| *score != i16::MIN)
+        .map(|score| score as i32)
+        .sum();
+}
+
+fn wishes(
+    data: &MatchingData,
+    values: &ElementValues,
+    connections: &Connections,
+    element: usize,
+    group: &[usize],
+) -> Vec<Wish> {
+    let id = connections.for_index(&element);
+    let mut wishes = Vec::new();
+    for (index, rule) in data.rules.iter().enumerate() {
+        let wanted = match rule.severity {
+            RuleSeverity::Prefer => true,
+            RuleSeverity::PreferExclude | RuleSeverity::ForceExclude => false,
+            _ => continue, // forced rules always hold and standard rules express no wish
+        };
+        let matches = |target: &String| {
+            *target != id && rule.in_scope(&id, target, values) && rule.check(&id, target, values)
+        };
+        let targets: Vec<String> = values
+            .ids()
+            .into_iter()
+            .filter(|target| matches(target))
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
+        let in_group = group
+            .iter()
+            .any(|other| targets.contains(&connections.for_index(other)));
+        wishes.push(Wish {
+            source: ScoreSource::Rule(index),
+            satisfied: in_group == wanted,
+        });
+    }
+    for (index, pair) in data.pairs.iter().enumerate() {
+        let other = if pair.first == id {
+            &pair.second
+        } else if pair.second == id {
+            &pair.first
+        } else {
+            continue;
+        };
+        let wanted = match pair.severity {
+            RuleSeverity::Force | RuleSeverity::Prefer => true,
+            RuleSeverity::Standard => pair.get_score() > 0,
+            RuleSeverity::PreferExclude | RuleSeverity::ForceExclude => false,
+        };
+        let in_group = group
+            .iter()
+            .any(|member| connections.for_index(member) == *other);
+        wishes.push(Wish {
+            source: ScoreSource::Pair(index),
+            satisfied: in_group == wanted,
+        });
+    }
+    return wishes;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::pairs::PairRule;
+    use crate::matching::verify_grouping;
+
+    fn metrics_of(groups: &[&[&str]]) -> Vec<ElementMetrics> {
+        let mut data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let mut pair = PairRule::new();
+        pair.first = "04".to_string();
+        pair.second = "06".to_string();
+        pair.severity = RuleSeverity::Prefer;
+        data.pairs.push(pair);
+        let groups: Vec<Vec<String>> = groups
+            .iter()
+            .map(|group| group.iter().map(|id| id.to_string()).collect())
+            .collect();
+        return verify_grouping(&data, &groups).unwrap().metrics;
+    }
+
+    #[test]
+    fn wishes_of_the_elements() {
+        let metrics = metrics_of(&[&["01", "02"], &["03", "06"], &["04", "05"]]);
+        let ids: Vec<&str> = metrics
+            .iter()
+            .map(|metric| metric.element.as_str())
+            .collect();
+        assert_eq!(ids, vec!["01", "02", "03", "04", "05", "06"]);
+        let wishes = |id: &str| {
+            metrics
+                .iter()
+                .find(|metric| metric.element == id)
+                .unwrap()
+                .wishes
+                .iter()
+                .map(|wish| (wish.source, wish.satisfied))
+                .collect::<Vec<(ScoreSource, bool)>>()
+        };
+        // Gabi wants to be with Tim but away from her department
+        assert_eq!(
+            wishes("02"),
+            vec![(ScoreSource::Rule(0), false), (ScoreSource::Rule(2), true)]
+        );
+        // Tobias is kept away from the other member of his team
+        assert_eq!(
+            wishes("03"),
+            vec![
+                (ScoreSource::Rule(0), false),
+                (ScoreSource::Rule(2), false),
+                (ScoreSource::Rule(3), true),
+            ]
+        );
+        assert_eq!(
+            wishes("04"),
+            vec![
+                (ScoreSource::Rule(0), false),
+                (ScoreSource::Rule(2), false),
+                (ScoreSource::Pair(0), false),
+            ]
+        );
+        let gabi = &metrics[1];
+        assert_eq!(gabi.satisfied(), 1);
+        assert_eq!(gabi.satisfaction(), 0.5);
+        assert_eq!(gabi.group, 0);
+    }
+
+    #[test]
+    fn rank_compares_the_points_of_the_other_groups() {
+        let metrics = metrics_of(&[&["01", "02"], &["03", "06"], &["04", "05"]]);
+        let received_and_rank = |id: &str| {
+            let metric = metrics.iter().find(|metric| metric.element == id).unwrap();
+            (metric.received, metric.rank)
+        };
+        assert_eq!(received_and_rank("02"), (1, 1)); // Gabi gets points for her wish from Tim
+                                                     // Tobias would lose nothing in the Aachen group, as excluded connections count as 0
+        assert_eq!(received_and_rank("03"), (-1, 2));
+        assert_eq!(received_and_rank("04"), (-1, 3));
+    }
+}
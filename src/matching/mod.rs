@@ -1,40 +1,109 @@
 extern crate itertools;
-extern crate regex;
 extern crate serde;
 extern crate serde_json;
 
 use self::itertools::Itertools;
 use crate::matching::connections::{Connections, Connector};
 use crate::matching::data::{MatchingData, MatchingResult};
+use crate::matching::graph::PreferenceGraph;
+use crate::matching::metrics::element_metrics;
+use crate::matching::schema::ElementValues;
+use crate::matching::validation::ValidationReport;
 use debug_print::debug_println;
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fmt;
 
 pub mod connections;
 pub mod data;
+pub mod diff;
+pub mod graph;
+pub mod grouping;
+pub mod history;
+pub mod layers;
+pub mod metrics;
+pub mod pairs;
+pub mod parser;
 pub mod rules;
+pub mod schema;
 pub mod score;
+pub mod sensitivity;
+pub mod validation;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum MatchingError {
+    InvalidData(ValidationReport), // the data has validation errors
+    Infeasible(String),            // no grouping satisfies the forced rules and the outputs
+    BudgetExceeded(usize),         // the search visited more than the given number of steps
+    Internal(String),
+}
+
+impl fmt::Display for MatchingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MatchingError::InvalidData(report) => {
+                write!(f, "The data is invalid:\n{}", report)
+            }
+            MatchingError::Infeasible(reason) => write!(f, "No grouping possible: {}", reason),
+            MatchingError::BudgetExceeded(steps) => write!(
+                f,
+                "Gave up after {} steps, try fewer elements or stricter rules",
+                steps
+            ),
+            MatchingError::Internal(message) => write!(f, "Internal error: {}", message),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SolverSettings {
+    pub max_steps: usize, // recursion steps per search before giving up
+}
+
+impl Default for SolverSettings {
+    fn default() -> Self {
+        Self {
+            max_steps: 1_000_000,
+        }
+    }
+}
+
+/// State shared by all recursion levels of one search.
+struct Search<'a> {
+    connections: &'a Connections,
+    settings: &'a SolverSettings,
+    steps: usize,
+}
 
 fn calc_max_combinations(
-    connections: &Connections,
+    search: &mut Search,
     possible_connections: &Vec<Vec<usize>>,
     ignore: HashSet<usize>,
-    outputs: &HashMap<usize, i16>, // (output size, available amount) | negative values are considered infinite
-) -> (i32, Vec<Vec<usize>>) {
+    outputs: &BTreeMap<usize, i16>, // (output size, available amount) | negative values are considered infinite
+) -> Result<(i32, Vec<Vec<usize>>), MatchingError> {
+    let connections = search.connections;
     if connections.len() == ignore.len() {
-        return (0, Vec::new()); // all elements have been visited, so we need an empty list to return
+        return Ok((0, Vec::new())); // all elements have been visited, so we need an empty list to return
+    }
+    search.steps += 1;
+    if search.steps > search.settings.max_steps {
+        return Err(MatchingError::BudgetExceeded(search.settings.max_steps));
     }
     let mut current_max: (i32, Vec<Vec<usize>>) = (i32::MIN, Vec::new());
-    let item = &possible_connections[0];
+    let item = match possible_connections.first() {
+        Some(item) => item,
+        None => return Ok((i32::MIN, Vec::new())),
+    };
     // debug_println!("{:?}", item);
     // debug_println!("    {:?}", possible_connections);
     // debug_println!("    {:?}", ignore);
     // debug_println!("    {:?}", outputs);
     if item.len() < 2 {
-        return (i32::MIN, Vec::new());
+        return Ok((i32::MIN, Vec::new()));
     }
     if ignore.contains(&item[0]) {
         return calc_max_combinations(
-            connections,
+            search,
             &possible_connections
                 .iter()
                 .skip(1)
@@ -51,11 +120,14 @@ fn calc_max_combinations(
         .map(|elem| *elem)
         .collect();
     if filtered_item.is_empty() {
-        return (i32::MIN, Vec::new());
+        return Ok((i32::MIN, Vec::new()));
     }
     let mut combinations: Vec<(i32, Vec<&usize>)> = Vec::new();
-    let max_output_size = outputs.keys().max().expect("No output available!");
-    for output in 2..(*max_output_size + 1) {
+    let max_output_size = match outputs.keys().max() {
+        Some(size) => *size,
+        None => return Ok((i32::MIN, Vec::new())), // all outputs are used up
+    };
+    for output in 2..(max_output_size + 1) {
         let combinations_of_size = filtered_item.iter().combinations(output - 1);
         // debug_println!("    {} -> {:?}", output - 1, combinations_of_size);
         for mut comb in combinations_of_size {
@@ -72,18 +144,13 @@ fn calc_max_combinations(
 
     for (score, comb) in combinations {
         let mut output_size = comb.len();
-        let mut output_opt = outputs.get(&output_size);
-        if output_opt.is_none() {
-            let mut keys: Vec<usize> = outputs
-                .keys()
-                .cloned()
-                .filter(|k| k > &comb.len())
-                .collect();
-            keys.sort();
-            output_size = keys[0];
-            output_opt = outputs.get(&output_size);
+        if !outputs.contains_key(&output_size) {
+            match outputs.keys().filter(|k| **k > comb.len()).min() {
+                Some(size) => output_size = *size,
+                None => continue, // no output is large enough for this group
+            }
         }
-        let output = output_opt.expect("No viable output found!");
+        let output = outputs[&output_size];
 
         let mut new_outputs = outputs.clone();
         match output {
@@ -100,7 +167,7 @@ fn calc_max_combinations(
             new_ignore.insert(*elem);
         }
         let mut scenario = calc_max_combinations(
-            connections,
+            search,
             &possible_connections
                 .iter()
                 .skip(1)
@@ -108,7 +175,7 @@ fn calc_max_combinations(
                 .collect(),
             new_ignore,
             &new_outputs,
-        );
+        )?;
         if scenario.0 == i32::MIN {
             continue;
         }
@@ -118,49 +185,177 @@ fn calc_max_combinations(
             current_max = scenario
         }
     }
-    return current_max;
+    return Ok(current_max);
 }
 
-pub fn process(matching_data: &MatchingData) -> MatchingResult {
-    let mut connections = Connections::from_data(&matching_data.elements);
+/// Validates the data and applies all rules, pairs and score layers to the connections between the elements.
+fn prepare(matching_data: &MatchingData) -> Result<(ElementValues, Connections), MatchingError> {
+    let report = matching_data.validate();
+    if report.has_errors() {
+        return Err(MatchingError::InvalidData(report));
+    }
+    let values = ElementValues::parse(matching_data).map_err(|errors| {
+        MatchingError::Internal(format!("{} values don't match the schema", errors.len()))
+    })?;
+    let mut connections = Connections::from_data(&values);
     //println!("{}", connections.to_string());
-    for rule in &matching_data.rules {
+    for (index, rule) in matching_data.rules.iter().enumerate() {
         //println!("{}", rule.to_string());
-        connections.apply(rule, &matching_data.elements);
+        connections.apply(index, rule, &values);
         //println!("{}", connections.to_string());
     }
+    for (index, pair) in matching_data.pairs.iter().enumerate() {
+        connections.apply_pair(index, pair);
+    }
+    for (index, layer) in matching_data.layers.iter().enumerate() {
+        connections.apply_layer(index, layer);
+    }
+    return Ok((values, connections));
+}
+
+/// Scores a grouping made outside of the tool and lists every constraint it violates.
+pub fn verify_grouping(
+    matching_data: &MatchingData,
+    groups: &Vec<Vec<String>>,
+) -> Result<MatchingResult, MatchingError> {
+    let (values, connections) = prepare(matching_data)?;
+    return Ok(grouping::verify(
+        matching_data,
+        &values,
+        &connections,
+        groups,
+    ));
+}
+
+/// Connections the rules and pairs create between the elements, e.g. to inspect them in graph tools.
+pub fn preference_graph(matching_data: &MatchingData) -> Result<PreferenceGraph, MatchingError> {
+    let (_, connections) = prepare(matching_data)?;
+    return Ok(PreferenceGraph::from_connections(&connections));
+}
 
+/// Searches the best grouping with the default solver settings.
+/// The UI records the settings of every run, so it uses `process_with_settings` directly.
+#[allow(dead_code)]
+pub fn process(matching_data: &MatchingData) -> Result<MatchingResult, MatchingError> {
+    return process_with_settings(matching_data, &SolverSettings::default());
+}
+
+pub fn process_with_settings(
+    matching_data: &MatchingData,
+    settings: &SolverSettings,
+) -> Result<MatchingResult, MatchingError> {
+    let (values, connections) = prepare(matching_data)?;
     debug_println!("preferences:");
     debug_println!("{}", connections.to_string());
 
     let mut possible_connections = connections.possible_connections();
     possible_connections.sort_by(|con1, con2| con1.len().cmp(&con2.len()));
+    let mut search = Search {
+        connections: &connections,
+        settings,
+        steps: 0,
+    };
     let (max_score, max_combinations) = calc_max_combinations(
-        &connections,
+        &mut search,
         &possible_connections,
         HashSet::new(),
         &matching_data.outputs,
-    );
+    )?;
     debug_println!("max score: {}", max_score);
     for comb in max_combinations {
         debug_println!("{:?}", comb);
     }
     possible_connections.sort_by(|con1, con2| con2.len().cmp(&con1.len()));
+    search.steps = 0;
     let (max_score, max_combinations) = calc_max_combinations(
-        &connections,
+        &mut search,
         &possible_connections,
         HashSet::new(),
         &matching_data.outputs,
-    );
+    )?;
     debug_println!("max score: {}", max_score);
     for comb in &max_combinations {
         debug_println!("{:?}", comb);
     }
-    return MatchingResult {
+    if max_score == i32::MIN {
+        return Err(MatchingError::Infeasible(
+            "the forced rules and the outputs can't be satisfied at the same time".to_string(),
+        ));
+    }
+    return Ok(MatchingResult {
         score: max_score,
         connections: max_combinations
             .iter()
             .map(|con| con.iter().map(|c| connections.for_index(c)).collect())
             .collect(),
-    };
+        groups: max_combinations
+            .iter()
+            .map(|con| connections.breakdown(con))
+            .collect(),
+        metrics: element_metrics(matching_data, &values, &connections, &max_combinations),
+        violations: Vec::new(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn process_uses_the_default_settings() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let result = process(&data).unwrap();
+        assert_eq!(
+            Ok(result.clone()),
+            process_with_settings(&data, &SolverSettings::default())
+        );
+        let mut grouped: Vec<&String> = result.connections.iter().flatten().collect();
+        grouped.sort();
+        assert_eq!(grouped, data.elements.keys().collect::<Vec<&String>>());
+    }
+
+    #[test]
+    fn errors_tell_why_processing_failed() {
+        match process(&MatchingData::new()) {
+            Err(MatchingError::InvalidData(report)) => assert!(report.has_errors()),
+            other => panic!("unexpected {:?}", other),
+        }
+
+        let mut data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let settings = SolverSettings { max_steps: 1 };
+        assert_eq!(
+            process_with_settings(&data, &settings),
+            Err(MatchingError::BudgetExceeded(1))
+        );
+
+        // everyone in Bonn works for DevOps, but they have to stay in Bonn
+        data.rules
+            .extend(parser::parse_rules("force-exclude: same(Abteilung)", &data.fields).unwrap());
+        assert!(matches!(process(&data), Err(MatchingError::Infeasible(_))));
+    }
+
+    #[test]
+    fn score_layers_add_to_the_connections() {
+        let mut data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        data.layers.push(layers::ScoreLayer::new(
+            "survey".to_string(),
+            vec![layers::PairScore {
+                from: "02".to_string(),
+                to: "03".to_string(),
+                score: 5,
+            }],
+        ));
+        let graph = preference_graph(&data).unwrap();
+        let edge = graph
+            .edges
+            .iter()
+            .find(|edge| edge.source == "02" && edge.target == "03")
+            .unwrap();
+        assert_eq!(edge.kind, graph::EdgeKind::Preferred);
+        assert_eq!(edge.score, Some(4));
+        assert!(!edge.mutual);
+    }
 }
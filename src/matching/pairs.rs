@@ -0,0 +1,94 @@
+use crate::matching::rules::RuleSeverity;
+use crate::matching::score::{weighted_score, Scorer};
+use serde::{Deserialize, Serialize};
+
+/// Constraint or preference between two specific elements, e.g. "Tim and Thomas must not be together".
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct PairRule {
+    pub(crate) first: String,  // element id
+    pub(crate) second: String, // element id
+    pub(crate) severity: RuleSeverity,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) weight: Option<i16>, // amount of points instead of the severity's, ignored for forced pairs
+}
+
+impl PairRule {
+    pub fn new() -> Self {
+        Self {
+            first: "".to_string(),
+            second: "".to_string(),
+            severity: RuleSeverity::ForceExclude,
+            weight: None,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        return !self.first.is_empty() && !self.second.is_empty() && self.first != self.second;
+    }
+}
+
+impl Scorer<i16> for PairRule {
+    fn get_score(&self) -> i16 {
+        return weighted_score(&self.severity, self.weight);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::data::MatchingData;
+
+    fn pair(severity: RuleSeverity, weight: Option<i16>) -> PairRule {
+        let mut pair = PairRule::new();
+        pair.first = "01".to_string();
+        pair.second = "02".to_string();
+        pair.severity = severity;
+        pair.weight = weight;
+        return pair;
+    }
+
+    #[test]
+    fn weight_keeps_the_sign_of_the_severity() {
+        assert_eq!(pair(RuleSeverity::Prefer, None).get_score(), 2);
+        assert_eq!(pair(RuleSeverity::Prefer, Some(5)).get_score(), 5);
+        assert_eq!(pair(RuleSeverity::PreferExclude, Some(5)).get_score(), -5);
+        assert_eq!(pair(RuleSeverity::PreferExclude, Some(-5)).get_score(), -5);
+        assert_eq!(pair(RuleSeverity::Standard, Some(-5)).get_score(), 5);
+    }
+
+    #[test]
+    fn validity() {
+        assert!(pair(RuleSeverity::Prefer, None).is_valid());
+        let mut same = pair(RuleSeverity::Prefer, None);
+        same.second = "01".to_string();
+        assert!(!same.is_valid());
+        assert!(!PairRule::new().is_valid());
+    }
+
+    #[test]
+    fn pairs_decide_the_grouping() {
+        let mut data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        data.rules.clear();
+        let mut forced = pair(RuleSeverity::Force, None);
+        forced.second = "04".to_string();
+        let mut excluded = pair(RuleSeverity::ForceExclude, None);
+        excluded.first = "02".to_string();
+        excluded.second = "05".to_string();
+        let mut preferred = pair(RuleSeverity::Prefer, Some(4));
+        preferred.first = "05".to_string();
+        preferred.second = "06".to_string();
+        data.pairs = vec![forced, excluded, preferred];
+
+        let result = crate::matching::process(&data).unwrap();
+        let group_of = |id: &str| {
+            result
+                .connections
+                .iter()
+                .position(|group| group.iter().any(|member| member == id))
+        };
+        assert_eq!(group_of("01"), group_of("04"));
+        assert_ne!(group_of("02"), group_of("05"));
+        assert_eq!(group_of("05"), group_of("06"));
+    }
+}
@@ -0,0 +1,826 @@
+//! Textual representation of rules.
+//!
+//! One rule per line, empty lines and everything after `#` are ignored:
+//!
+//! ```text
+//! prefer-exclude: same(Abteilung)
+//! force: same(Standort)
+//! prefer 3: Wunschpartner matches Name
+//! force-exclude: same(Team)
+//! prefer: same(Team) when source Standort = Aachen and target Standort = Aachen
+//! prefer: Standort = 'Bonn' with Standort = 'Bonn'
+//! force-exclude: Team = 'Chef' with Team = 'Devs'
+//! prefer-exclude: any with Team = 'Chef'
+//! prefer 5: references(Wunschpartner)
+//! prefer 3: same(Abteilung) and not same(Team)
+//! ```
+//!
+//! The optional number after the severity sets the amount of points instead of the severity's default,
+//! the sign comes from the severity, so `prefer-exclude 3` subtracts 3 points.
+//! Fields can be referenced by id (`group2`) or by their display name (`Abteilung`),
+//! names and values containing spaces or other special characters have to be quoted (`"Wunsch partner"`).
+//! Single quotes mark literals a field is compared to instead of the field of the other element,
+//! `with` separates the source and the target side and `any` leaves the source side unrestricted.
+//! For `force` rules the source side selects the elements the rule applies to, e.g. `force: Team = 'Chef' with Team = 'Devs'`
+//! only keeps the members of Chef away from everyone outside of Devs.
+//! The optional `when` clause limits the rule to pairs whose `source`, `target` or `both` elements
+//! fulfill all given conditions.
+//!
+//! Further comparisons can be added with `and`, the rule only applies to pairs fulfilling all of them.
+//! `not` inverts a comparison, e.g. `prefer 3: same(Abteilung) and not same(Team)`.
+
+use crate::matching::rules::{
+    Comparing, Comparison, Rule, RuleOperand, RuleSeverity, RuleSide, ScopeCondition,
+};
+use indexmap::IndexMap;
+use std::fmt;
+use std::ops::Range;
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleParseError {
+    pub message: String,
+    pub span: Range<usize>, // byte offsets into the parsed text
+}
+
+impl RuleParseError {
+    fn new(message: String, span: Range<usize>) -> Self {
+        Self { message, span }
+    }
+
+    /// Line and column (both starting at 1) of the start of the error.
+    pub fn position(&self, source: &str) -> (usize, usize) {
+        let before = &source[..self.span.start.min(source.len())];
+        let line_start = before.rfind('\n').map(|index| index + 1).unwrap_or(0);
+        let line = before.matches('\n').count() + 1;
+        return (line, before[line_start..].chars().count() + 1);
+    }
+
+    /// Renders the error together with the offending line and a marker below the span.
+    pub fn render(&self, source: &str) -> String {
+        let (line, column) = self.position(source);
+        let start = self.span.start.min(source.len());
+        let line_start = source[..start]
+            .rfind('\n')
+            .map(|index| index + 1)
+            .unwrap_or(0);
+        let text = source[line_start..].lines().next().unwrap_or("");
+        let end = (self.span.end - line_start).min(text.len());
+        let start = (start - line_start).min(end);
+        return format!(
+            "{}:{}: {}\n{}\n{}{}",
+            line,
+            column,
+            self.message,
+            text,
+            " ".repeat(column - 1),
+            "^".repeat(text[start..end].chars().count().max(1))
+        );
+    }
+}
+
+impl fmt::Display for RuleParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} (at {}..{})",
+            self.message, self.span.start, self.span.end
+        )
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Word(String),
+    Quoted(String),
+    Literal(String),
+    Number(i64),
+    Colon,
+    OpenParen,
+    CloseParen,
+    Equals,
+    Less,
+    Greater,
+}
+
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Token::Word(word) => write!(f, "'{}'", word),
+            Token::Quoted(text) => write!(f, "\"{}\"", text),
+            Token::Literal(text) => write!(f, "'{}'", text),
+            Token::Number(number) => write!(f, "{}", number),
+            Token::Colon => write!(f, "':'"),
+            Token::OpenParen => write!(f, "'('"),
+            Token::CloseParen => write!(f, "')'"),
+            Token::Equals => write!(f, "'='"),
+            Token::Less => write!(f, "'<'"),
+            Token::Greater => write!(f, "'>'"),
+        }
+    }
+}
+
+fn is_word_char(c: char) -> bool {
+    return c.is_alphanumeric() || c == '_' || c == '-' || c == '.';
+}
+
+fn tokenize(line: &str, offset: usize) -> Result<Vec<(Token, Range<usize>)>, RuleParseError> {
+    let mut tokens = Vec::new();
+    let mut chars = line.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        let token = match c {
+            '#' => break,
+            _ if c.is_whitespace() => continue,
+            ':' => Token::Colon,
+            '(' => Token::OpenParen,
+            ')' => Token::CloseParen,
+            '=' => Token::Equals,
+            '<' => Token::Less,
+            '>' => Token::Greater,
+            '"' | '\'' => {
+                let quote = c;
+                let mut text = String::new();
+                let mut closed = false;
+                while let Some((_, c)) = chars.next() {
+                    match c {
+                        _ if c == quote => {
+                            closed = true;
+                            break;
+                        }
+                        '\\' => {
+                            if let Some((_, escaped)) = chars.next() {
+                                text.push(escaped);
+                            }
+                        }
+                        _ => text.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(RuleParseError::new(
+                        format!(
+                            "Unterminated {}",
+                            if quote == '"' {
+                                "quoted name"
+                            } else {
+                                "literal"
+                            }
+                        ),
+                        offset + start..offset + line.len(),
+                    ));
+                }
+                if quote == '"' {
+                    Token::Quoted(text)
+                } else {
+                    Token::Literal(text)
+                }
+            }
+            _ if is_word_char(c) => {
+                let mut word = c.to_string();
+                while let Some((_, next)) = chars.peek() {
+                    if !is_word_char(*next) {
+                        break;
+                    }
+                    word.push(*next);
+                    chars.next();
+                }
+                match word.parse::<i64>() {
+                    Ok(number) if !word.starts_with('0') || word == "0" => Token::Number(number),
+                    _ => Token::Word(word),
+                }
+            }
+            _ => {
+                return Err(RuleParseError::new(
+                    format!("Unexpected character '{}'", c),
+                    offset + start..offset + start + c.len_utf8(),
+                ))
+            }
+        };
+        let end = chars.peek().map(|(index, _)| *index).unwrap_or(line.len());
+        tokens.push((token, offset + start..offset + end));
+    }
+    return Ok(tokens);
+}
+
+struct Parser<'a> {
+    tokens: Vec<(Token, Range<usize>)>,
+    position: usize,
+    end: usize,
+    fields: &'a IndexMap<String, String>,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&(Token, Range<usize>)> {
+        return self.tokens.get(self.position);
+    }
+
+    fn next(&mut self, expected: &str) -> Result<(Token, Range<usize>), RuleParseError> {
+        match self.tokens.get(self.position) {
+            Some(token) => {
+                self.position += 1;
+                Ok(token.clone())
+            }
+            None => Err(RuleParseError::new(
+                format!("Expected {} but the rule ended", expected),
+                self.end..self.end,
+            )),
+        }
+    }
+
+    fn expect(&mut self, expected: Token) -> Result<(), RuleParseError> {
+        let (token, span) = self.next(&expected.to_string())?;
+        if token != expected {
+            return Err(RuleParseError::new(
+                format!("Expected {} but found {}", expected, token),
+                span,
+            ));
+        }
+        return Ok(());
+    }
+
+    fn severity(&mut self) -> Result<RuleSeverity, RuleParseError> {
+        let (token, span) = self.next("a severity")?;
+        if let Token::Word(word) = &token {
+            if let Some(severity) = parse_severity(word) {
+                return Ok(severity);
+            }
+        }
+        return Err(RuleParseError::new(
+            format!(
+                "Unknown severity {}, expected one of {}",
+                token,
+                RuleSeverity::values()
+                    .iter()
+                    .map(|severity| print_severity(severity))
+                    .collect::<Vec<&str>>()
+                    .join(", ")
+            ),
+            span,
+        ));
+    }
+
+    fn weight(&mut self) -> Result<Option<i16>, RuleParseError> {
+        if let Some((Token::Number(number), span)) = self.peek().cloned() {
+            self.position += 1;
+            if number < i16::MIN as i64 || number > i16::MAX as i64 {
+                return Err(RuleParseError::new(
+                    format!("Weight {} is out of range", number),
+                    span,
+                ));
+            }
+            return Ok(Some(number as i16));
+        }
+        return Ok(None);
+    }
+
+    fn field(&mut self) -> Result<String, RuleParseError> {
+        let (token, span) = self.next("a field")?;
+        let name = match token {
+            Token::Word(word) => word,
+            Token::Quoted(text) => text,
+            Token::Number(number) => number.to_string(),
+            _ => {
+                return Err(RuleParseError::new(
+                    format!("Expected a field but found {}", token),
+                    span,
+                ))
+            }
+        };
+        if name.is_empty() {
+            return Err(RuleParseError::new("Field name is empty".to_string(), span));
+        }
+        return resolve_field(&name, self.fields)
+            .map_err(|message| RuleParseError::new(message, span));
+    }
+
+    fn operand(&mut self) -> Result<RuleOperand, RuleParseError> {
+        let (token, span) = self.next("an operand")?;
+        let operand = match &token {
+            Token::Equals => Some(RuleOperand::Match),
+            Token::Less => Some(RuleOperand::Less),
+            Token::Greater => Some(RuleOperand::Greater),
+            Token::Word(word) => parse_operand(word),
+            _ => None,
+        };
+        return operand.ok_or(RuleParseError::new(
+            format!(
+                "Unknown operand {}, expected one of 'matches', 'includes', 'less-than', 'greater-than', 'intersects', 'contains-all', 'overlaps', 'references'",
+                token
+            ),
+            span,
+        ));
+    }
+
+    fn keyword(&mut self, keyword: &str) -> bool {
+        if let Some((Token::Word(word), _)) = self.peek() {
+            if word.eq_ignore_ascii_case(keyword) {
+                self.position += 1;
+                return true;
+            }
+        }
+        return false;
+    }
+
+    fn expect_keyword(&mut self, keyword: &str) -> Result<(), RuleParseError> {
+        if self.keyword(keyword) {
+            return Ok(());
+        }
+        let (token, span) = self.next(&format!("'{}'", keyword))?;
+        return Err(RuleParseError::new(
+            format!("Expected '{}' but found {}", keyword, token),
+            span,
+        ));
+    }
+
+    fn value(&mut self) -> Result<String, RuleParseError> {
+        let (token, span) = self.next("a value")?;
+        return match token {
+            Token::Word(word) => Ok(word),
+            Token::Quoted(text) | Token::Literal(text) => Ok(text),
+            Token::Number(number) => Ok(number.to_string()),
+            _ => Err(RuleParseError::new(
+                format!("Expected a value but found {}", token),
+                span,
+            )),
+        };
+    }
+
+    fn literal(&mut self) -> Result<String, RuleParseError> {
+        let (token, span) = self.next("a literal")?;
+        return match token {
+            Token::Literal(text) => Ok(text),
+            _ => Err(RuleParseError::new(
+                format!("Expected a literal in single quotes but found {}", token),
+                span,
+            )),
+        };
+    }
+
+    fn sides(&mut self) -> Result<Vec<RuleSide>, RuleParseError> {
+        let (token, span) = self.next("'source', 'target' or 'both'")?;
+        if let Token::Word(word) = &token {
+            match &*word.to_lowercase() {
+                "source" => return Ok(vec![RuleSide::Source]),
+                "target" => return Ok(vec![RuleSide::Target]),
+                "both" => return Ok(RuleSide::values()),
+                _ => {}
+            }
+        }
+        return Err(RuleParseError::new(
+            format!("Expected 'source', 'target' or 'both' but found {}", token),
+            span,
+        ));
+    }
+
+    fn conditions(&mut self) -> Result<Vec<ScopeCondition>, RuleParseError> {
+        let mut conditions = Vec::new();
+        loop {
+            let sides = self.sides()?;
+            let field = self.field()?;
+            let operand = self.operand()?;
+            let value = self.value()?;
+            for side in sides {
+                conditions.push(ScopeCondition {
+                    side,
+                    field: field.clone(),
+                    operand,
+                    value: value.clone(),
+                });
+            }
+            if !self.keyword("and") {
+                return Ok(conditions);
+            }
+        }
+    }
+
+    /// A single comparison of the fields or literals of a pair, optionally negated with `not`.
+    fn comparison(&mut self) -> Result<Comparison, RuleParseError> {
+        let mut comparison = Comparison {
+            negated: self.keyword("not"),
+            field: "".to_string(),
+            target_field: "".to_string(),
+            operand: RuleOperand::Match,
+            value: None,
+            target_value: None,
+        };
+        if self.keyword("same") {
+            self.expect(Token::OpenParen)?;
+            comparison.field = self.field()?;
+            comparison.target_field = comparison.field.clone();
+            comparison.operand = RuleOperand::Match;
+            self.expect(Token::CloseParen)?;
+        } else if self.keyword("references") {
+            self.expect(Token::OpenParen)?;
+            comparison.field = self.field()?;
+            comparison.target_field = comparison.field.clone();
+            comparison.operand = RuleOperand::References;
+            self.expect(Token::CloseParen)?;
+        } else if self.keyword("any") {
+            self.expect_keyword("with")?;
+            comparison.target_field = self.field()?;
+            comparison.operand = self.operand()?;
+            comparison.target_value = Some(self.literal()?);
+        } else {
+            comparison.field = self.field()?;
+            let start = self.position;
+            comparison.operand = self.operand()?;
+            if comparison.operand == RuleOperand::References {
+                return Err(RuleParseError::new(
+                    "Use references(field) to refer to other elements".to_string(),
+                    self.tokens[start].1.clone(),
+                ));
+            }
+            if let Some((Token::Literal(_), _)) = self.peek() {
+                comparison.value = Some(self.literal()?);
+                if self.keyword("with") {
+                    comparison.target_field = self.field()?;
+                    let start = self.position;
+                    if self.operand()? != comparison.operand {
+                        return Err(RuleParseError::new(
+                            "Both sides of a rule have to use the same operand".to_string(),
+                            self.tokens[start].1.clone(),
+                        ));
+                    }
+                    comparison.target_value = Some(self.literal()?);
+                }
+            } else {
+                comparison.target_field = self.field()?;
+            }
+        }
+        return Ok(comparison);
+    }
+
+    fn rule(&mut self) -> Result<Rule, RuleParseError> {
+        let mut rule = Rule::new();
+        rule.severity = self.severity()?;
+        rule.weight = self.weight()?;
+        self.expect(Token::Colon)?;
+        let comparison = self.comparison()?;
+        rule.negated = comparison.negated;
+        rule.field = comparison.field;
+        rule.target_field = comparison.target_field;
+        rule.operand = comparison.operand;
+        rule.value = comparison.value;
+        rule.target_value = comparison.target_value;
+        while self.keyword("and") {
+            rule.comparisons.push(self.comparison()?);
+        }
+        if self.keyword("when") {
+            rule.scope = self.conditions()?;
+        }
+        if let Some((token, span)) = self.peek() {
+            return Err(RuleParseError::new(
+                format!("Unexpected {} after the end of the rule", token),
+                span.clone(),
+            ));
+        }
+        return Ok(rule);
+    }
+}
+
+fn normalize(word: &str) -> String {
+    return word.to_lowercase().replace(['-', '_'], "");
+}
+
+fn parse_severity(word: &str) -> Option<RuleSeverity> {
+    return RuleSeverity::values()
+        .into_iter()
+        .find(|severity| normalize(&severity.to_string()) == normalize(word));
+}
+
+fn print_severity(severity: &RuleSeverity) -> &'static str {
+    return match severity {
+        RuleSeverity::Force => "force",
+        RuleSeverity::Prefer => "prefer",
+        RuleSeverity::Standard => "standard",
+        RuleSeverity::PreferExclude => "prefer-exclude",
+        RuleSeverity::ForceExclude => "force-exclude",
+    };
+}
+
+fn parse_operand(word: &str) -> Option<RuleOperand> {
+    return match &*normalize(word) {
+        "match" | "matches" => Some(RuleOperand::Match),
+        "include" | "includes" => Some(RuleOperand::Include),
+        "less" | "lessthan" => Some(RuleOperand::Less),
+        "greater" | "greaterthan" => Some(RuleOperand::Greater),
+        "intersect" | "intersects" => Some(RuleOperand::Intersects),
+        "containsall" => Some(RuleOperand::ContainsAll),
+        "overlap" | "overlaps" => Some(RuleOperand::Overlap),
+        "reference" | "references" => Some(RuleOperand::References),
+        _ => None,
+    };
+}
+
+fn print_operand(operand: &RuleOperand) -> &'static str {
+    return match operand {
+        RuleOperand::Match => "matches",
+        RuleOperand::Include => "includes",
+        RuleOperand::Less => "less-than",
+        RuleOperand::Greater => "greater-than",
+        RuleOperand::Intersects => "intersects",
+        RuleOperand::ContainsAll => "contains-all",
+        RuleOperand::Overlap => "overlaps",
+        RuleOperand::References => "references",
+    };
+}
+
+/// Resolves a field by id first and by display name second.
+/// Without any known fields every name is taken as id.
+fn resolve_field(name: &str, fields: &IndexMap<String, String>) -> Result<String, String> {
+    if fields.is_empty() || fields.contains_key(name) {
+        return Ok(name.to_string());
+    }
+    let mut candidates: Vec<&String> = fields
+        .iter()
+        .filter(|(_, display_name)| display_name.as_str() == name)
+        .map(|(id, _)| id)
+        .collect();
+    if candidates.is_empty() {
+        candidates = fields
+            .iter()
+            .filter(|(_, display_name)| display_name.to_lowercase() == name.to_lowercase())
+            .map(|(id, _)| id)
+            .collect();
+    }
+    candidates.sort();
+    return match candidates.len() {
+        0 => Err(format!("Unknown field '{}'", name)),
+        1 => Ok(candidates[0].clone()),
+        _ => Err(format!(
+            "Field name '{}' is ambiguous, use one of the ids {}",
+            name,
+            candidates
+                .iter()
+                .map(|id| id.as_str())
+                .collect::<Vec<&str>>()
+                .join(", ")
+        )),
+    };
+}
+
+/// Parses a whole document with one rule per line, `fields` (id -> name) is used to resolve field names to ids.
+pub fn parse_rules(
+    text: &str,
+    fields: &IndexMap<String, String>,
+) -> Result<Vec<Rule>, RuleParseError> {
+    let mut rules = Vec::new();
+    let mut offset = 0;
+    for line in text.split('\n') {
+        if let Some(rule) = parse_line(line, offset, fields)? {
+            rules.push(rule);
+        }
+        offset += line.len() + 1;
+    }
+    return Ok(rules);
+}
+
+fn parse_line(
+    line: &str,
+    offset: usize,
+    fields: &IndexMap<String, String>,
+) -> Result<Option<Rule>, RuleParseError> {
+    let tokens = tokenize(line, offset)?;
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+    let mut parser = Parser {
+        end: tokens.last().map(|(_, span)| span.end).unwrap_or(offset),
+        tokens,
+        position: 0,
+        fields,
+    };
+    return parser.rule().map(Some);
+}
+
+fn is_keyword(word: &str) -> bool {
+    return parse_operand(word).is_some()
+        || [
+            "same", "when", "and", "not", "with", "any", "source", "target", "both",
+        ]
+        .iter()
+        .any(|keyword| word.eq_ignore_ascii_case(keyword));
+}
+
+fn print_text(text: &str) -> String {
+    let bare = !text.is_empty()
+        && text.chars().all(is_word_char)
+        && text.parse::<i64>().is_err()
+        && !is_keyword(text);
+    if bare {
+        return text.to_string();
+    }
+    return format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""));
+}
+
+fn print_literal(text: &str) -> String {
+    return format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"));
+}
+
+fn print_field(id: &str, fields: &IndexMap<String, String>) -> String {
+    return print_text(match fields.get(id) {
+        // only use the display name if it resolves back to the same field
+        Some(name) if resolve_field(name, fields).ok().as_deref() == Some(id) => name.as_str(),
+        _ => id,
+    });
+}
+
+/// Prints the conditions of a rule scope, without the leading `when`.
+pub fn print_scope(scope: &[ScopeCondition], fields: &IndexMap<String, String>) -> String {
+    return scope
+        .iter()
+        .map(|condition| {
+            format!(
+                "{} {} {} {}",
+                condition.side.to_string().to_lowercase(),
+                print_field(&condition.field, fields),
+                print_operand(&condition.operand),
+                print_text(&condition.value)
+            )
+        })
+        .collect::<Vec<String>>()
+        .join(" and ");
+}
+
+/// Prints a single comparison of a rule, including a leading `not`.
+fn print_comparison(comparison: &dyn Comparing, fields: &IndexMap<String, String>) -> String {
+    let text = if comparison.is_literal() {
+        let source = match comparison.value() {
+            Some(value) => format!(
+                "{} {} {}",
+                print_field(comparison.field(), fields),
+                print_operand(&comparison.operand()),
+                print_literal(value)
+            ),
+            None => "any".to_string(),
+        };
+        match comparison.target_value() {
+            Some(target_value) => format!(
+                "{} with {} {} {}",
+                source,
+                print_field(comparison.target_field(), fields),
+                print_operand(&comparison.operand()),
+                print_literal(target_value)
+            ),
+            None => source,
+        }
+    } else if comparison.operand() == RuleOperand::References {
+        format!("references({})", print_field(comparison.field(), fields))
+    } else if comparison.field() == comparison.target_field()
+        && comparison.operand() == RuleOperand::Match
+    {
+        format!("same({})", print_field(comparison.field(), fields))
+    } else {
+        format!(
+            "{} {} {}",
+            print_field(comparison.field(), fields),
+            print_operand(&comparison.operand()),
+            print_field(comparison.target_field(), fields)
+        )
+    };
+    return match comparison.negated() {
+        true => format!("not {}", text),
+        false => text,
+    };
+}
+
+/// Prints the further comparisons and the scope of a rule, e.g. `and not same(Team) when source Team = Devs`.
+pub fn print_conditions(rule: &Rule, fields: &IndexMap<String, String>) -> String {
+    let mut parts: Vec<String> = rule
+        .comparisons
+        .iter()
+        .map(|comparison| format!("and {}", print_comparison(comparison, fields)))
+        .collect();
+    if !rule.scope.is_empty() {
+        parts.push(format!("when {}", print_scope(&rule.scope, fields)));
+    }
+    return parts.join(" ");
+}
+
+/// Prints a rule so that `parse_rules` with the same `fields` returns it again.
+pub fn print_rule(rule: &Rule, fields: &IndexMap<String, String>) -> String {
+    let mut text = print_severity(&rule.severity).to_string();
+    if let Some(weight) = rule.weight {
+        text += &format!(" {}", weight);
+    }
+    text += ": ";
+    text += &print_comparison(rule, fields);
+    let conditions = print_conditions(rule, fields);
+    if !conditions.is_empty() {
+        text += " ";
+        text += &conditions;
+    }
+    return text;
+}
+
+pub fn print_rules(rules: &[Rule], fields: &IndexMap<String, String>) -> String {
+    return rules
+        .iter()
+        .map(|rule| print_rule(rule, fields) + "\n")
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::data::MatchingData;
+
+    fn fields(names: &[(&str, &str)]) -> IndexMap<String, String> {
+        return names
+            .iter()
+            .map(|(id, name)| (id.to_string(), name.to_string()))
+            .collect();
+    }
+
+    #[test]
+    fn concept_rules_round_trip() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let parsed = parse_rules(include_str!("../../res/concept.rules"), &data.fields).unwrap();
+        assert_eq!(parsed, data.rules);
+        let printed = print_rules(&data.rules, &data.fields);
+        assert_eq!(parse_rules(&printed, &data.fields).unwrap(), data.rules);
+    }
+
+    #[test]
+    fn keyword_and_numeric_names_are_quoted() {
+        let fields = fields(&[("f1", "and"), ("f2", "42"), ("f3", "Team name")]);
+        let mut same = Rule::new();
+        same.field = "f1".to_string();
+        same.target_field = "f1".to_string();
+        same.operand = RuleOperand::Match;
+        let mut compare = Rule::new();
+        compare.field = "f2".to_string();
+        compare.target_field = "f3".to_string();
+        compare.operand = RuleOperand::Less;
+        let rules = vec![same, compare];
+        let printed = print_rules(&rules, &fields);
+        assert!(printed.contains("same(\"and\")"), "{}", printed);
+        assert!(
+            printed.contains("\"42\" less-than \"Team name\""),
+            "{}",
+            printed
+        );
+        assert_eq!(parse_rules(&printed, &fields).unwrap(), rules);
+    }
+
+    #[test]
+    fn literals_with_escapes() {
+        let fields = fields(&[("t", "Team")]);
+        let rules = parse_rules(
+            r"prefer: Team = 'O\'Brien \\ Co' with Team = 'Devs'",
+            &fields,
+        )
+        .unwrap();
+        assert_eq!(rules[0].value.as_deref(), Some(r"O'Brien \ Co"));
+        assert_eq!(rules[0].target_value.as_deref(), Some("Devs"));
+        let printed = print_rules(&rules, &fields);
+        assert_eq!(parse_rules(&printed, &fields).unwrap(), rules);
+    }
+
+    #[test]
+    fn weight_out_of_range() {
+        let text = "prefer 40000: same(Team)";
+        let error = parse_rules(text, &fields(&[("t", "Team")])).unwrap_err();
+        assert_eq!(error.message, "Weight 40000 is out of range");
+        assert_eq!(&text[error.span.clone()], "40000");
+    }
+
+    #[test]
+    fn combined_comparisons() {
+        let fields = fields(&[("a", "Abteilung"), ("t", "Team")]);
+        let text = "prefer 3: same(Abteilung) and not same(Team)";
+        let rules = parse_rules(text, &fields).unwrap();
+        assert_eq!(rules[0].field, "a");
+        assert!(!rules[0].negated);
+        assert_eq!(rules[0].comparisons.len(), 1);
+        assert!(rules[0].comparisons[0].negated);
+        assert_eq!(rules[0].comparisons[0].field, "t");
+        assert_eq!(print_rule(&rules[0], &fields), text);
+
+        let text = "force: not Team matches 'Chef' with Team matches 'Devs' and Abteilung matches Team when source Team matches Chef";
+        let rules = parse_rules(text, &fields).unwrap();
+        assert!(rules[0].negated);
+        assert_eq!(rules[0].scope.len(), 1);
+        assert_eq!(print_rule(&rules[0], &fields), text);
+
+        let error = parse_rules("prefer: same(Team) and", &fields).unwrap_err();
+        assert_eq!(error.message, "Expected a field but the rule ended");
+    }
+
+    #[test]
+    fn error_rendering_with_multibyte_text_and_crlf() {
+        let fields = fields(&[("g", "Größe"), ("t", "Team")]);
+        let text = "prefer: same(Team)\r\nprefer: Größe ? Team\r\n";
+        let error = parse_rules(text, &fields).unwrap_err();
+        assert_eq!(error.position(text), (2, 15));
+        assert_eq!(
+            error.render(text),
+            "2:15: Unexpected character '?'\nprefer: Größe ? Team\n              ^"
+        );
+        let text = "force: same(Größe)\r\nprefer: 'Größe\r\n";
+        let error = parse_rules(text, &fields).unwrap_err();
+        assert_eq!(
+            error.render(text),
+            "2:9: Unterminated literal\nprefer: 'Größe\n        ^^^^^^"
+        );
+    }
+}
@@ -1,12 +1,12 @@
-use crate::matching::rules::RuleOperand::{Include, Match};
+use crate::matching::rules::RuleOperand::{
+    ContainsAll, Greater, Include, Intersects, Less, Match, Overlap, References,
+};
 use crate::matching::rules::RuleSeverity::{Force, ForceExclude, Prefer, PreferExclude, Standard};
-use crate::matching::score::Scorer;
+use crate::matching::schema::{ElementValues, ParsedValue, Value};
+use crate::matching::score::{weighted_score, Scorer};
 use derivative::Derivative;
-use regex;
-use regex::Regex;
 use serde::{Deserialize, Serialize};
-use std::collections::hash_map::RandomState;
-use std::collections::HashMap;
+use std::cmp::Ordering;
 use std::fmt;
 
 #[derive(Derivative, Serialize, Deserialize, Clone, Debug, PartialEq)]
@@ -19,6 +19,24 @@ pub struct Rule {
     pub(crate) target_field: String,
     #[derivative(Default(value = "RuleOperand::MATCH"))]
     pub(crate) operand: RuleOperand,
+    #[derivative(Default(value = "None"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) weight: Option<i16>, // amount of points instead of the severity's, ignored for forced rules
+    #[derivative(Default(value = "Vec::new()"))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) scope: Vec<ScopeCondition>, // rule only applies to pairs fulfilling all conditions
+    #[derivative(Default(value = "None"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) value: Option<String>, // literal the source field is compared to instead of the target field
+    #[derivative(Default(value = "None"))]
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) target_value: Option<String>, // literal the target field is compared to instead of the source field
+    #[derivative(Default(value = "false"))]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) negated: bool, // the rule applies to pairs for which the comparison does not hold
+    #[derivative(Default(value = "Vec::new()"))]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub(crate) comparisons: Vec<Comparison>, // further comparisons joined with `and`, all of them have to hold
 }
 
 impl Rule {
@@ -28,11 +46,227 @@ impl Rule {
             field: "".to_string(),
             target_field: "".to_string(),
             operand: RuleOperand::Match,
+            weight: None,
+            scope: Vec::new(),
+            value: None,
+            target_value: None,
+            negated: false,
+            comparisons: Vec::new(),
         }
     }
 
     pub fn is_valid(&self) -> bool {
-        return !self.field.is_empty() && !self.target_field.is_empty();
+        return self.has_fields()
+            && self
+                .comparisons
+                .iter()
+                .all(|comparison| comparison.has_fields())
+            && self
+                .scope
+                .iter()
+                .all(|condition| !condition.field.is_empty());
+    }
+
+    /// The comparison of the rule itself followed by the further ones.
+    pub fn all_comparisons(&self) -> Vec<&dyn Comparing> {
+        let mut comparisons: Vec<&dyn Comparing> = vec![self];
+        comparisons.extend(
+            self.comparisons
+                .iter()
+                .map(|comparison| comparison as &dyn Comparing),
+        );
+        return comparisons;
+    }
+
+    /// Forced rules only apply to the elements matching the literal of their source side,
+    /// the partners of those elements have to fulfill the rest of the rule.
+    fn selects(&self, id: &str, values: &ElementValues) -> bool {
+        return match &self.value {
+            Some(literal) => {
+                self.operand.count(
+                    field_value(values, id, &self.field),
+                    &values.literal(&self.field, literal),
+                ) > 0
+            }
+            None => true,
+        };
+    }
+}
+
+/// Comparison a rule makes in addition to its own one, e.g. `not same(Team)` in `same(Abteilung) and not same(Team)`.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Comparison {
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub(crate) negated: bool,
+    pub(crate) field: String,
+    pub(crate) target_field: String,
+    pub(crate) operand: RuleOperand,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) value: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub(crate) target_value: Option<String>,
+}
+
+/// Fields, literals and operand one element of a pair is compared to the other with.
+pub trait Comparing {
+    fn negated(&self) -> bool;
+    fn field(&self) -> &str;
+    fn target_field(&self) -> &str;
+    fn operand(&self) -> RuleOperand;
+    fn value(&self) -> Option<&str>;
+    fn target_value(&self) -> Option<&str>;
+
+    /// Whether both fields are compared to each other, or at least one of them to a literal.
+    fn is_literal(&self) -> bool {
+        return self.value().is_some() || self.target_value().is_some();
+    }
+
+    fn has_fields(&self) -> bool {
+        return match (self.value(), self.target_value()) {
+            _ if self.operand() == RuleOperand::References => !self.field().is_empty(),
+            (None, None) => !self.field().is_empty() && !self.target_field().is_empty(),
+            (value, target_value) => {
+                (value.is_none() || !self.field().is_empty())
+                    && (target_value.is_none() || !self.target_field().is_empty())
+            }
+        };
+    }
+
+    /// Fields the comparison reads, the unused side of a literal comparison is left out.
+    fn compared_fields(&self) -> Vec<&str> {
+        let mut fields = Vec::new();
+        if self.value().is_some() || self.target_value().is_none() {
+            fields.push(self.field());
+        }
+        if self.target_value().is_some()
+            || (self.value().is_none() && self.operand() != RuleOperand::References)
+        {
+            fields.push(self.target_field());
+        }
+        return fields;
+    }
+
+    /// How often the comparison holds for the pair, only `Overlap` holds more than once.
+    fn count_matches(&self, id: &str, target: &str, values: &ElementValues) -> usize {
+        let count = if self.operand() == RuleOperand::References && !self.is_literal() {
+            values
+                .references(id, self.field())
+                .iter()
+                .any(|reference| reference == target) as usize
+        } else if !self.is_literal() {
+            self.operand().count(
+                field_value(values, id, self.field()),
+                field_value(values, target, self.target_field()),
+            )
+        } else {
+            let source_count = match self.value() {
+                Some(literal) => self.operand().count(
+                    field_value(values, id, self.field()),
+                    &values.literal(self.field(), literal),
+                ),
+                None => usize::MAX,
+            };
+            let target_count = match self.target_value() {
+                Some(literal) => self.operand().count(
+                    field_value(values, target, self.target_field()),
+                    &values.literal(self.target_field(), literal),
+                ),
+                None => usize::MAX,
+            };
+            source_count.min(target_count)
+        };
+        return match self.negated() {
+            true => (count == 0) as usize,
+            false => count,
+        };
+    }
+}
+
+impl Comparing for Rule {
+    fn negated(&self) -> bool {
+        return self.negated;
+    }
+    fn field(&self) -> &str {
+        return &self.field;
+    }
+    fn target_field(&self) -> &str {
+        return &self.target_field;
+    }
+    fn operand(&self) -> RuleOperand {
+        return self.operand;
+    }
+    fn value(&self) -> Option<&str> {
+        return self.value.as_deref();
+    }
+    fn target_value(&self) -> Option<&str> {
+        return self.target_value.as_deref();
+    }
+}
+
+impl Comparing for Comparison {
+    fn negated(&self) -> bool {
+        return self.negated;
+    }
+    fn field(&self) -> &str {
+        return &self.field;
+    }
+    fn target_field(&self) -> &str {
+        return &self.target_field;
+    }
+    fn operand(&self) -> RuleOperand {
+        return self.operand;
+    }
+    fn value(&self) -> Option<&str> {
+        return self.value.as_deref();
+    }
+    fn target_value(&self) -> Option<&str> {
+        return self.target_value.as_deref();
+    }
+}
+
+static MISSING_VALUE: ParsedValue = ParsedValue {
+    value: Value::Text(String::new()),
+    items: Vec::new(),
+};
+
+/// Missing values are reported by the validation, an empty text is used in case they slip through.
+fn field_value<'a>(values: &'a ElementValues, id: &str, field: &str) -> &'a ParsedValue {
+    return values.parsed(id, field).unwrap_or(&MISSING_VALUE);
+}
+
+/// Condition on a single element of a pair, e.g. "source Standort matches Aachen".
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScopeCondition {
+    pub(crate) side: RuleSide,
+    pub(crate) field: String,
+    pub(crate) operand: RuleOperand,
+    pub(crate) value: String,
+}
+
+impl ScopeCondition {
+    pub fn is_met(&self, id: &str, target: &str, values: &ElementValues) -> bool {
+        let element = match self.side {
+            RuleSide::Source => id,
+            RuleSide::Target => target,
+        };
+        return match values.parsed(element, &self.field) {
+            Some(value) => self
+                .operand
+                .compare(value, &values.literal(&self.field, &self.value)),
+            None => false,
+        };
+    }
+}
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum RuleSide {
+    Source,
+    Target,
+}
+
+impl RuleSide {
+    pub fn values() -> Vec<Self> {
+        return vec![RuleSide::Source, RuleSide::Target];
     }
 }
 
@@ -45,7 +279,7 @@ pub enum RuleSeverity {
     ForceExclude,
 }
 
-impl<'a> RuleSeverity {
+impl RuleSeverity {
     pub fn values() -> Vec<Self> {
         return vec![Force, Prefer, Standard, PreferExclude, ForceExclude];
     }
@@ -55,94 +289,169 @@ impl<'a> RuleSeverity {
 pub enum RuleOperand {
     Match,
     Include,
+    Less,
+    Greater,
+    Intersects,  // at least one item in common
+    ContainsAll, // all items of the target are part of the value
+    Overlap,     // applied once for every item in common
+    References,  // the reference field of the source contains the target element
 }
 
-impl<'a> RuleOperand {
+impl RuleOperand {
     pub fn values() -> Vec<Self> {
-        return vec![Match, Include];
+        return vec![
+            Match,
+            Include,
+            Less,
+            Greater,
+            Intersects,
+            ContainsAll,
+            Overlap,
+            References,
+        ];
+    }
+
+    /// How often the operand applies to the values, only `Overlap` applies more than once.
+    pub fn count(&self, value: &ParsedValue, target_value: &ParsedValue) -> usize {
+        return match self {
+            RuleOperand::Overlap => {
+                let mut shared = target_value
+                    .items
+                    .iter()
+                    .filter(|item| !item.is_empty() && value.items.contains(item))
+                    .collect::<Vec<_>>();
+                shared.sort();
+                shared.dedup();
+                shared.len()
+            }
+            _ => self.compare(value, target_value) as usize,
+        };
+    }
+
+    pub fn compare(&self, value: &ParsedValue, target_value: &ParsedValue) -> bool {
+        return match self {
+            RuleOperand::Match => match (&value.value, &target_value.value) {
+                (Value::List(items), Value::List(other)) => {
+                    let mut items = items.clone();
+                    let mut other = other.clone();
+                    items.sort();
+                    other.sort();
+                    items == other
+                }
+                (value, target_value)
+                    if std::mem::discriminant(value) == std::mem::discriminant(target_value) =>
+                {
+                    value == target_value
+                }
+                (value, target_value) => value.to_string() == target_value.to_string(),
+            },
+            RuleOperand::Include | RuleOperand::ContainsAll => target_value
+                .items
+                .iter()
+                .all(|item| value.items.contains(item)),
+            RuleOperand::Intersects | RuleOperand::Overlap | RuleOperand::References => {
+                target_value
+                    .items
+                    .iter()
+                    .any(|item| !item.is_empty() && value.items.contains(item))
+            }
+            RuleOperand::Less => value.value.ordering(&target_value.value) == Some(Ordering::Less),
+            RuleOperand::Greater => {
+                value.value.ordering(&target_value.value) == Some(Ordering::Greater)
+            }
+        };
     }
 }
 
 pub trait RuleActions {
-    fn check(
-        &self,
-        id: &str,
-        target: &str,
-        values: &HashMap<String, HashMap<String, String>>,
-    ) -> bool;
-    fn apply(&self, x: usize, y: usize, connections: &mut Vec<Vec<i16>>);
+    fn check(&self, id: &str, target: &str, values: &ElementValues) -> bool;
+    fn count(&self, id: &str, target: &str, values: &ElementValues) -> usize;
+    fn in_scope(&self, id: &str, target: &str, values: &ElementValues) -> bool;
+    /// Applies the rule to a cell and returns the points added, forced rules add none.
+    fn apply(&self, x: usize, y: usize, times: usize, connections: &mut Vec<Vec<i16>>) -> i16;
     fn check_and_apply(
         &self,
         id: &str,
         target: &str,
-        values: &HashMap<String, HashMap<String, String>>,
+        values: &ElementValues,
         x: usize,
         y: usize,
         connections: &mut Vec<Vec<i16>>,
-    );
+    ) -> i16;
 }
 
 impl RuleActions for Rule {
-    fn check(
-        &self,
-        id: &str,
-        target: &str,
-        values: &HashMap<String, HashMap<String, String>>,
-    ) -> bool {
-        let value = values
-            .get(id)
-            .expect(&*format!("No values found for target {}", id))
-            .get(&*self.field)
-            .expect(&*format!(
-                "Target {} has no value for {}",
-                id, self.target_field
-            ))
-            .trim();
-        let target_value = values
-            .get(target)
-            .expect(&*format!("No values found for target {}", target))
-            .get(&*self.target_field)
-            .expect(&*format!(
-                "Target {} has no value for {}",
-                target, self.target_field
-            ))
-            .trim();
-        return match self.operand {
-            RuleOperand::Match => value == target_value,
-            RuleOperand::Include => Regex::new(&*format!(
-                "^(.*, *)?{}( *,.*|$)",
-                regex::escape(target_value)
-            ))
-            .unwrap()
-            .is_match(value),
-        };
+    fn check(&self, id: &str, target: &str, values: &ElementValues) -> bool {
+        return self.count(id, target, values) > 0;
+    }
+
+    /// How often the comparison of the rule holds, 0 if any of the further comparisons doesn't.
+    fn count(&self, id: &str, target: &str, values: &ElementValues) -> usize {
+        let count = self.count_matches(id, target, values);
+        if count == 0
+            || !self
+                .comparisons
+                .iter()
+                .all(|comparison| comparison.count_matches(id, target, values) > 0)
+        {
+            return 0;
+        }
+        return count;
     }
 
-    fn apply(&self, x: usize, y: usize, connections: &mut Vec<Vec<i16>>) {
+    fn in_scope(&self, id: &str, target: &str, values: &ElementValues) -> bool {
+        return self
+            .scope
+            .iter()
+            .all(|condition| condition.is_met(id, target, values));
+    }
+
+    fn apply(&self, x: usize, y: usize, times: usize, connections: &mut Vec<Vec<i16>>) -> i16 {
+        let before = connections[x][y];
         match self.severity {
-            RuleSeverity::Force | RuleSeverity::ForceExclude => connections[x][y] = i16::MIN, // same because force is inverted to exclude all others
-            _ => connections[x][y] += self.severity.get_score() as i16,
+            RuleSeverity::Force | RuleSeverity::ForceExclude => {
+                connections[x][y] = i16::MIN; // same because force is inverted to exclude all others
+                return 0;
+            }
+            _ if before == i16::MIN => return 0, // stays excluded
+            _ => {
+                let score = self
+                    .get_score()
+                    .saturating_mul(times.min(i16::MAX as usize) as i16);
+                connections[x][y] = before.saturating_add(score).max(i16::MIN + 1);
+            }
         };
+        return connections[x][y].saturating_sub(before);
     }
 
     fn check_and_apply(
         &self,
         id: &str,
         target: &str,
-        values: &HashMap<String, HashMap<String, String, RandomState>, RandomState>,
+        values: &ElementValues,
         x: usize,
         y: usize,
         connections: &mut Vec<Vec<i16>>,
-    ) {
-        let check = self.check(id, target, values);
+    ) -> i16 {
+        if self.severity == RuleSeverity::Force && !self.selects(id, values) {
+            return 0;
+        }
+        let count = self.count(id, target, values);
         if match self.severity {
-            RuleSeverity::Force => !check, // invert to exclude all non matching
-            _ => check,
+            RuleSeverity::Force => count == 0, // invert to exclude all non matching
+            _ => count > 0,
         } {
-            self.apply(x, y, connections)
+            return self.apply(x, y, count.max(1), connections);
         }
+        return 0;
     }
 }
+impl Scorer<i16> for Rule {
+    fn get_score(&self) -> i16 {
+        return weighted_score(&self.severity, self.weight);
+    }
+}
+
 impl fmt::Display for Rule {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
@@ -157,8 +466,151 @@ impl fmt::Display for RuleSeverity {
         write!(f, "{:?}", self)
     }
 }
+impl fmt::Display for RuleSide {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
 impl fmt::Display for RuleOperand {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(f, "{:?}", self)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::matching::data::{MatchingData, RawValue};
+    use crate::matching::parser::parse_rules;
+    use crate::matching::prepare;
+    use crate::matching::schema::FieldType;
+
+    fn concept() -> MatchingData {
+        return serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+    }
+
+    /// Score of the connection from one element of res/concept.json to another under the given rules.
+    fn cell(rules: &str, from: &str, to: &str) -> i16 {
+        return cell_in(concept(), rules, from, to);
+    }
+
+    fn cell_in(mut data: MatchingData, rules: &str, from: &str, to: &str) -> i16 {
+        data.rules = parse_rules(rules, &data.fields).unwrap();
+        let (_, connections) = prepare(&data).unwrap();
+        return connections.score(
+            connections.index_of(from).unwrap(),
+            connections.index_of(to).unwrap(),
+        );
+    }
+
+    #[test]
+    fn weight_keeps_the_sign_of_the_severity() {
+        assert_eq!(cell("standard: same(Team)", "01", "03"), 2);
+        assert_eq!(cell("prefer 3: same(Team)", "01", "03"), 4);
+        assert_eq!(cell("prefer-exclude: same(Team)", "01", "03"), -1);
+        assert_eq!(cell("prefer-exclude 3: same(Team)", "01", "03"), -2);
+        assert_eq!(cell("prefer-exclude -3: same(Team)", "01", "03"), -2);
+        assert_eq!(cell("prefer -3: same(Team)", "01", "03"), 4);
+        assert_eq!(cell("prefer-exclude 3: same(Team)", "01", "02"), 1);
+    }
+
+    #[test]
+    fn literals_select_the_elements_of_forced_rules() {
+        let rule = "force: Team = 'Chef' with Team = 'Devs'";
+        assert_eq!(cell(rule, "06", "01"), 1); // Chef with Devs
+        assert_eq!(cell(rule, "06", "02"), i16::MIN); // Chef with another team
+        assert_eq!(cell(rule, "01", "02"), 1); // the rule only applies to Chef
+        assert_eq!(cell(rule, "02", "04"), 1);
+        let rule = "force: any with Team = 'Devs'";
+        assert_eq!(cell(rule, "02", "01"), 1);
+        assert_eq!(cell(rule, "02", "04"), i16::MIN);
+        let rule = "force: Team = 'Chef'";
+        assert_eq!(cell(rule, "06", "02"), 1);
+        assert_eq!(cell(rule, "01", "02"), 1);
+    }
+
+    #[test]
+    fn literals_of_preferring_and_excluding_rules() {
+        let rule = "prefer: Team = 'Chef' with Team = 'Devs'";
+        assert_eq!(cell(rule, "06", "01"), 3);
+        assert_eq!(cell(rule, "01", "06"), 1);
+        assert_eq!(cell(rule, "06", "02"), 1);
+        assert_eq!(cell("prefer: Team = 'Chef'", "06", "02"), 3);
+        assert_eq!(cell("prefer: any with Team = 'Chef'", "02", "06"), 3);
+        assert_eq!(cell("prefer: any with Team = 'Chef'", "06", "02"), 1);
+        let rule = "force-exclude: Team = 'Chef' with Team = 'Devs'";
+        assert_eq!(cell(rule, "06", "01"), i16::MIN);
+        assert_eq!(cell(rule, "06", "02"), 1);
+        assert_eq!(cell(rule, "01", "06"), 1);
+        assert_eq!(
+            cell("force-exclude: any with Team = 'Chef'", "02", "06"),
+            i16::MIN
+        );
+        assert_eq!(cell("force-exclude: any with Team = 'Chef'", "06", "02"), 1);
+    }
+
+    #[test]
+    fn combined_comparisons_have_to_hold_together() {
+        let rule = "prefer 3: same(Abteilung) and not same(Team)";
+        assert_eq!(cell(rule, "01", "02"), 4); // same department, other team
+        assert_eq!(cell(rule, "01", "03"), 1); // same department and team
+        assert_eq!(cell(rule, "01", "04"), 1); // other department
+        assert_eq!(cell("prefer: not same(Abteilung)", "01", "04"), 3);
+        assert_eq!(cell("prefer: not same(Abteilung)", "01", "02"), 1);
+    }
+
+    #[test]
+    fn scope_limits_the_rule_to_the_matching_elements() {
+        let rule = "prefer: same(Abteilung) when both Standort = Bonn";
+        assert_eq!(cell(rule, "01", "02"), 3);
+        assert_eq!(cell(rule, "04", "05"), 1);
+        let rule = "prefer: same(Abteilung) when source Standort = Aachen";
+        assert_eq!(cell(rule, "04", "05"), 3);
+        assert_eq!(cell(rule, "01", "02"), 1);
+        let rule = "prefer: same(Abteilung) when target Team = Devs";
+        assert_eq!(cell(rule, "02", "01"), 3);
+        assert_eq!(cell(rule, "01", "02"), 1);
+        let rule = "force-exclude: same(Abteilung) when source Team = Chef";
+        assert_eq!(cell(rule, "06", "01"), i16::MIN);
+        assert_eq!(cell(rule, "01", "06"), 1);
+    }
+
+    #[test]
+    fn list_operands_compare_the_items() {
+        let mut data = concept();
+        data.schema.insert("group5".to_string(), FieldType::List);
+        for (id, teams) in [
+            ("01", vec!["Devs", "Ops"]),
+            ("02", vec!["Ops", "QA"]),
+            ("03", vec!["Devs"]),
+            ("04", vec!["Devs", "Ops", "QA"]),
+        ] {
+            let teams = teams.iter().map(|team| team.to_string()).collect();
+            data.elements[id].insert("group5".to_string(), RawValue::List(teams));
+        }
+        let rule = "prefer: Team intersects Team";
+        assert_eq!(cell_in(data.clone(), rule, "01", "02"), 3);
+        assert_eq!(cell_in(data.clone(), rule, "03", "02"), 1);
+        let rule = "prefer: Team contains-all Team";
+        assert_eq!(cell_in(data.clone(), rule, "01", "03"), 3);
+        assert_eq!(cell_in(data.clone(), rule, "03", "01"), 1);
+        let rule = "prefer: Team overlaps Team";
+        assert_eq!(cell_in(data.clone(), rule, "04", "01"), 5);
+        assert_eq!(cell_in(data.clone(), rule, "04", "03"), 3);
+        assert_eq!(cell_in(data, "prefer: Team includes 'QA'", "02", "01"), 3);
+    }
+
+    #[test]
+    fn references_connect_the_referenced_elements() {
+        let mut data = concept();
+        data.schema
+            .insert("group4".to_string(), FieldType::Reference);
+        let rule = "prefer 5: references(Wunschpartner)";
+        assert_eq!(cell_in(data.clone(), rule, "02", "01"), 6); // Gabi wishes for Tim
+        assert_eq!(cell_in(data.clone(), rule, "01", "02"), 6); // Tim wishes for Gabi
+        assert_eq!(cell_in(data.clone(), rule, "01", "03"), 1);
+        assert_eq!(
+            cell_in(data, "prefer 5: not references(Wunschpartner)", "02", "01"),
+            1
+        );
+    }
+}
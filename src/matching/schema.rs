@@ -0,0 +1,516 @@
+use crate::matching::data::{MatchingData, RawValue};
+use crate::matching::rules::RuleOperand;
+use indexmap::IndexMap;
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::cmp::Ordering;
+use std::collections::HashMap;
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Copy, Clone, PartialEq, Debug)]
+pub enum FieldType {
+    Text,
+    Number,
+    List,
+    Boolean,
+    Date,
+    Reference, // names or ids of other elements, resolved to ids
+}
+
+impl FieldType {
+    pub fn values() -> Vec<Self> {
+        return vec![
+            FieldType::Text,
+            FieldType::Number,
+            FieldType::List,
+            FieldType::Boolean,
+            FieldType::Date,
+            FieldType::Reference,
+        ];
+    }
+
+    /// Operands that make sense for values of this type.
+    pub fn operands(&self) -> Vec<RuleOperand> {
+        return match self {
+            FieldType::Text => vec![RuleOperand::Match, RuleOperand::Include],
+            FieldType::Number | FieldType::Date => {
+                vec![RuleOperand::Match, RuleOperand::Less, RuleOperand::Greater]
+            }
+            FieldType::List => vec![
+                RuleOperand::Match,
+                RuleOperand::Include,
+                RuleOperand::Intersects,
+                RuleOperand::ContainsAll,
+                RuleOperand::Overlap,
+            ],
+            FieldType::Boolean => vec![RuleOperand::Match],
+            FieldType::Reference => vec![
+                RuleOperand::References,
+                RuleOperand::Match,
+                RuleOperand::Intersects,
+            ],
+        };
+    }
+
+    /// Parses a stored value, lists are accepted as JSON arrays or as comma separated text.
+    pub fn parse_raw(&self, raw: &RawValue) -> Result<Value, String> {
+        return match (self, raw) {
+            (_, RawValue::Text(text)) => self.parse(text),
+            (FieldType::List | FieldType::Text, RawValue::List(items)) => Ok(Value::List(
+                split_items(items.iter().map(|item| item.as_str())),
+            )),
+            (FieldType::Reference, RawValue::List(items)) => Ok(Value::Reference(split_items(
+                items.iter().map(|item| item.as_str()),
+            ))),
+            (_, RawValue::List(_)) => Err(format!("Lists are not allowed for {} fields", self)),
+        };
+    }
+
+    pub fn parse(&self, raw: &str) -> Result<Value, String> {
+        let raw = raw.trim();
+        if raw.is_empty() {
+            return Ok(Value::Text("".to_string()));
+        }
+        return match self {
+            FieldType::Text => Ok(Value::Text(raw.to_string())),
+            FieldType::Number => raw
+                .replace(',', ".")
+                .parse::<f64>()
+                .map(Value::Number)
+                .map_err(|_| format!("'{}' is not a number", raw)),
+            FieldType::List => Ok(Value::List(split_items(raw.split(',')))),
+            FieldType::Boolean => match &*raw.to_lowercase() {
+                "true" | "yes" | "ja" | "x" | "1" => Ok(Value::Boolean(true)),
+                "false" | "no" | "nein" | "0" => Ok(Value::Boolean(false)),
+                _ => Err(format!("'{}' is not a boolean", raw)),
+            },
+            FieldType::Date => parse_date(raw).map(Value::Date).ok_or(format!(
+                "'{}' is not a date (YYYY-MM-DD or DD.MM.YYYY)",
+                raw
+            )),
+            FieldType::Reference => Ok(Value::Reference(split_items(raw.split(',')))),
+        };
+    }
+}
+
+fn split_items<'a>(items: impl Iterator<Item = &'a str>) -> Vec<String> {
+    return items
+        .map(|item| item.trim().to_string())
+        .filter(|item| !item.is_empty())
+        .collect();
+}
+
+fn parse_date(raw: &str) -> Option<(i32, u32, u32)> {
+    let parts: Vec<&str> = raw.split(['-', '.']).collect();
+    if parts.len() != 3 {
+        return None;
+    }
+    let (year, month, day) = if raw.contains('-') {
+        (parts[0], parts[1], parts[2])
+    } else {
+        (parts[2], parts[1], parts[0])
+    };
+    let date = (
+        year.parse::<i32>().ok()?,
+        month.parse::<u32>().ok()?,
+        day.parse::<u32>().ok()?,
+    );
+    if date.1 < 1 || date.1 > 12 || date.2 < 1 || date.2 > 31 {
+        return None;
+    }
+    return Some(date);
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Text(String),
+    Number(f64),
+    List(Vec<String>),
+    Boolean(bool),
+    Date((i32, u32, u32)),  // year, month, day
+    Reference(Vec<String>), // ids of the referenced elements
+}
+
+impl Value {
+    /// Single items the value consists of, comma separated text is split for backwards compatibility.
+    pub fn items(&self) -> Vec<Cow<'_, str>> {
+        return match self {
+            Value::List(items) | Value::Reference(items) => {
+                items.iter().map(|item| Cow::from(item.as_str())).collect()
+            }
+            Value::Text(text) => text.split(',').map(|item| Cow::from(item.trim())).collect(),
+            _ => vec![Cow::from(self.to_string())],
+        };
+    }
+
+    /// Order of numbers and dates, other values can't be ordered.
+    pub fn ordering(&self, other: &Value) -> Option<Ordering> {
+        return match (self, other) {
+            (Value::Number(number), Value::Number(other)) => number.partial_cmp(other),
+            (Value::Date(date), Value::Date(other)) => Some(date.cmp(other)),
+            _ => None,
+        };
+    }
+}
+
+/// Value together with its single items, split once so rules don't split texts for every pair.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParsedValue {
+    pub value: Value,
+    pub items: Vec<String>,
+}
+
+impl ParsedValue {
+    pub fn new(value: Value) -> Self {
+        let items = value
+            .items()
+            .into_iter()
+            .map(|item| item.into_owned())
+            .collect();
+        return Self { value, items };
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Text(text) => write!(f, "{}", text),
+            Value::Number(number) => write!(f, "{}", number),
+            Value::List(items) | Value::Reference(items) => write!(f, "{}", items.join(", ")),
+            Value::Boolean(boolean) => write!(f, "{}", boolean),
+            Value::Date((year, month, day)) => write!(f, "{:04}-{:02}-{:02}", year, month, day),
+        }
+    }
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaError {
+    pub element: String,
+    pub field: String,
+    pub message: String,
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} / {}: {}", self.element, self.field, self.message)
+    }
+}
+
+/// Element values parsed according to the schema, so rules don't have to parse strings for every pair.
+pub struct ElementValues {
+    values: HashMap<String, HashMap<String, ParsedValue>>, // id -> { fieldId -> value }
+    schema: IndexMap<String, FieldType>,
+    names: HashMap<String, Vec<String>>, // lowercase name -> ids of elements with that name
+    literals: HashMap<String, HashMap<String, ParsedValue>>, // fieldId -> { literal -> value }, the literals of the rules
+    pub(crate) warnings: Vec<SchemaError>, // references that could not be resolved and were dropped
+}
+
+impl ElementValues {
+    pub fn parse(data: &MatchingData) -> Result<Self, Vec<SchemaError>> {
+        let mut errors = Vec::new();
+        let mut values = HashMap::new();
+        for (id, element) in &data.elements {
+            let mut parsed = HashMap::new();
+            for (field, raw) in element {
+                match data.field_type(field).parse_raw(raw) {
+                    Ok(value) => {
+                        parsed.insert(field.clone(), value);
+                    }
+                    Err(message) => errors.push(SchemaError {
+                        element: id.clone(),
+                        field: field.clone(),
+                        message,
+                    }),
+                }
+            }
+            values.insert(id.clone(), parsed);
+        }
+        if !errors.is_empty() {
+            errors.sort_by(|a, b| (&a.element, &a.field).cmp(&(&b.element, &b.field)));
+            return Err(errors);
+        }
+        let mut names: HashMap<String, Vec<String>> = HashMap::new();
+        if let Some(name_field) = data.name_field() {
+            for (id, element) in &data.elements {
+                if let Some(name) = element.get(name_field) {
+                    names
+                        .entry(name.to_string().trim().to_lowercase())
+                        .or_default()
+                        .push(id.clone());
+                }
+            }
+        }
+        let warnings = resolve_references(&mut values, &names);
+        let mut element_values = Self {
+            values: values
+                .into_iter()
+                .map(|(id, element)| {
+                    let element = element
+                        .into_iter()
+                        .map(|(field, value)| (field, ParsedValue::new(value)))
+                        .collect();
+                    (id, element)
+                })
+                .collect(),
+            schema: data.schema.clone(),
+            names,
+            literals: HashMap::new(),
+            warnings,
+        };
+        for rule in &data.rules {
+            for comparison in rule.all_comparisons() {
+                if let Some(value) = comparison.value() {
+                    element_values.add_literal(comparison.field(), value);
+                }
+                if let Some(value) = comparison.target_value() {
+                    element_values.add_literal(comparison.target_field(), value);
+                }
+            }
+            for condition in &rule.scope {
+                element_values.add_literal(&condition.field, &condition.value);
+            }
+        }
+        return Ok(element_values);
+    }
+
+    fn add_literal(&mut self, field: &str, raw: &str) {
+        let literal = self.parse_literal(field, raw);
+        self.literals
+            .entry(field.to_string())
+            .or_default()
+            .insert(raw.to_string(), literal);
+    }
+
+    pub fn ids(&self) -> Vec<String> {
+        let mut ids: Vec<String> = self.values.keys().cloned().collect();
+        ids.sort();
+        return ids;
+    }
+
+    pub fn get(&self, id: &str, field: &str) -> Option<&Value> {
+        return self.parsed(id, field).map(|parsed| &parsed.value);
+    }
+
+    pub fn parsed(&self, id: &str, field: &str) -> Option<&ParsedValue> {
+        return self.values.get(id)?.get(field);
+    }
+
+    /// Ids of the elements referenced by the given field of an element.
+    pub fn references(&self, id: &str, field: &str) -> &[String] {
+        return match self.get(id, field) {
+            Some(Value::Reference(references)) => references,
+            _ => &[],
+        };
+    }
+
+    /// Literal of a rule parsed like a value of the given field, literals of the parsed data are only parsed once.
+    pub fn literal(&self, field: &str, raw: &str) -> Cow<'_, ParsedValue> {
+        return match self
+            .literals
+            .get(field)
+            .and_then(|literals| literals.get(raw))
+        {
+            Some(literal) => Cow::Borrowed(literal),
+            None => Cow::Owned(self.parse_literal(field, raw)),
+        };
+    }
+
+    /// Parses a literal like a value of the given field, falling back to text.
+    fn parse_literal(&self, field: &str, raw: &str) -> ParsedValue {
+        let value = self
+            .schema
+            .get(field)
+            .unwrap_or(&FieldType::Text)
+            .parse(raw)
+            .unwrap_or(Value::Text(raw.trim().to_string()));
+        return ParsedValue::new(match value {
+            Value::Reference(references) => Value::Reference(
+                references
+                    .iter()
+                    .filter_map(|reference| {
+                        resolve_reference(reference, &self.values, &self.names).ok()
+                    })
+                    .collect(),
+            ),
+            _ => value,
+        });
+    }
+}
+
+/// Replaces the names in reference fields by the ids of the elements they refer to.
+fn resolve_references(
+    values: &mut HashMap<String, HashMap<String, Value>>,
+    names: &HashMap<String, Vec<String>>,
+) -> Vec<SchemaError> {
+    let mut warnings = Vec::new();
+    let ids: HashMap<String, ()> = values.keys().map(|id| (id.clone(), ())).collect();
+    for (id, element) in values.iter_mut() {
+        for (field, value) in element.iter_mut() {
+            if let Value::Reference(references) = value {
+                let mut resolved = Vec::new();
+                for reference in references.iter() {
+                    match resolve_reference(reference, &ids, names) {
+                        Ok(target) => resolved.push(target),
+                        Err(message) => warnings.push(SchemaError {
+                            element: id.clone(),
+                            field: field.clone(),
+                            message,
+                        }),
+                    }
+                }
+                *references = resolved;
+            }
+        }
+    }
+    warnings.sort_by(|a, b| (&a.element, &a.field).cmp(&(&b.element, &b.field)));
+    return warnings;
+}
+
+/// Resolves a reference by element id first and by the name of the element second.
+fn resolve_reference<T>(
+    reference: &str,
+    ids: &HashMap<String, T>,
+    names: &HashMap<String, Vec<String>>,
+) -> Result<String, String> {
+    if ids.contains_key(reference) {
+        return Ok(reference.to_string());
+    }
+    return match names.get(&reference.to_lowercase()) {
+        Some(candidates) if candidates.len() == 1 => Ok(candidates[0].clone()),
+        Some(candidates) => {
+            let mut candidates = candidates.clone();
+            candidates.sort();
+            Err(format!(
+                "'{}' is ambiguous, use one of the ids {}",
+                reference,
+                candidates.join(", ")
+            ))
+        }
+        None => Err(format!("'{}' does not refer to any element", reference)),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::parser::parse_rules;
+
+    fn data(schema: &[(&str, FieldType)], elements: &[(&str, &[(&str, &str)])]) -> MatchingData {
+        let mut data = MatchingData::new();
+        for (field, field_type) in schema {
+            data.fields.insert(field.to_string(), field.to_string());
+            data.schema.insert(field.to_string(), *field_type);
+        }
+        for (id, values) in elements {
+            let values = values
+                .iter()
+                .map(|(field, value)| (field.to_string(), RawValue::Text(value.to_string())))
+                .collect();
+            data.elements.insert(id.to_string(), values);
+        }
+        return data;
+    }
+
+    #[test]
+    fn typed_values() {
+        assert_eq!(FieldType::Number.parse(" 1,5 "), Ok(Value::Number(1.5)));
+        assert!(FieldType::Number.parse("many").is_err());
+        assert_eq!(FieldType::Boolean.parse("Ja"), Ok(Value::Boolean(true)));
+        assert_eq!(FieldType::Boolean.parse("no"), Ok(Value::Boolean(false)));
+        assert!(FieldType::Boolean.parse("maybe").is_err());
+        assert_eq!(
+            FieldType::Date.parse("01.02.2020"),
+            FieldType::Date.parse("2020-02-01")
+        );
+        assert!(FieldType::Date.parse("2020-13-01").is_err());
+        assert_eq!(
+            FieldType::List.parse("a, b,,c"),
+            Ok(Value::List(vec!["a".into(), "b".into(), "c".into()]))
+        );
+        assert_eq!(FieldType::Number.parse(""), Ok(Value::Text("".into())));
+        assert!(FieldType::Boolean
+            .parse_raw(&RawValue::List(vec!["true".into()]))
+            .is_err());
+    }
+
+    #[test]
+    fn invalid_values_are_reported_per_element_and_field() {
+        let data = data(
+            &[("age", FieldType::Number)],
+            &[("02", &[("age", "old")]), ("01", &[("age", "x")])],
+        );
+        let errors = ElementValues::parse(&data).err().unwrap();
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].to_string(), "01 / age: 'x' is not a number");
+    }
+
+    #[test]
+    fn items_are_split_once() {
+        let data = data(&[("tags", FieldType::Text)], &[("01", &[("tags", "a, b")])]);
+        let values = ElementValues::parse(&data).unwrap();
+        let parsed = values.parsed("01", "tags").unwrap();
+        assert_eq!(parsed.value, Value::Text("a, b".into()));
+        assert_eq!(parsed.items, vec!["a".to_string(), "b".to_string()]);
+    }
+
+    #[test]
+    fn literals_of_the_rules_are_parsed_once() {
+        let mut data = data(&[("age", FieldType::Number)], &[("01", &[("age", "30")])]);
+        data.rules = parse_rules(
+            "prefer: age greater-than '18' when source age less-than 65",
+            &data.fields,
+        )
+        .unwrap();
+        let values = ElementValues::parse(&data).unwrap();
+        assert!(matches!(values.literal("age", "18"), Cow::Borrowed(_)));
+        assert!(matches!(values.literal("age", "65"), Cow::Borrowed(_)));
+        assert_eq!(values.literal("age", "18").value, Value::Number(18.0));
+        let other = values.literal("age", "40");
+        assert!(matches!(other, Cow::Owned(_)));
+        assert_eq!(other.value, Value::Number(40.0));
+    }
+
+    #[test]
+    fn references_are_resolved_by_id_and_name() {
+        let mut data = data(
+            &[("name", FieldType::Text), ("buddies", FieldType::Reference)],
+            &[
+                ("01", &[("name", "Anna"), ("buddies", "Ben, 03")]),
+                ("02", &[("name", "Ben"), ("buddies", "anna")]),
+                ("03", &[("name", "Carl"), ("buddies", "Nobody")]),
+                ("04", &[("name", "Dora"), ("buddies", "carl")]),
+                ("05", &[("name", "Carl")]),
+            ],
+        );
+        let values = ElementValues::parse(&data).unwrap();
+        assert_eq!(values.references("01", "buddies"), ["02", "03"]);
+        assert_eq!(values.references("02", "buddies"), ["01"]);
+        assert!(values.references("03", "buddies").is_empty());
+        assert_eq!(
+            values
+                .warnings
+                .iter()
+                .map(|warning| warning.to_string())
+                .collect::<Vec<String>>(),
+            vec![
+                "03 / buddies: 'Nobody' does not refer to any element",
+                "04 / buddies: 'carl' is ambiguous, use one of the ids 03, 05",
+            ]
+        );
+
+        // moving another field to the front keeps the chosen name field
+        data.move_field("buddies", -1);
+        assert_eq!(
+            ElementValues::parse(&data)
+                .unwrap()
+                .references("01", "buddies"),
+            ["02", "03"]
+        );
+    }
+}
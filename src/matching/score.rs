@@ -1,5 +1,6 @@
 use crate::matching::rules::RuleSeverity;
 use crate::matching::rules::RuleSeverity::*;
+use serde::{Deserialize, Serialize};
 
 pub(crate) trait Scorer<T> {
     fn get_score(&self) -> T;
@@ -16,3 +17,113 @@ impl Scorer<i8> for RuleSeverity {
         };
     }
 }
+
+/// Score of a severity with an optional weight, the weight sets the amount but the severity keeps its sign,
+/// so a weighted exclude still lowers the connection.
+pub(crate) fn weighted_score(severity: &RuleSeverity, weight: Option<i16>) -> i16 {
+    let score = severity.get_score() as i16;
+    return match weight {
+        Some(weight) => score.signum() * weight.saturating_abs(),
+        None => score,
+    };
+}
+
+/// Where points of a connection come from.
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum ScoreSource {
+    Base,         // every connection starts with the standard score
+    Rule(usize),  // index of the rule
+    Pair(usize),  // index of the pair constraint
+    Layer(usize), // index of the imported score layer
+}
+
+/// Points one source contributed to the connection from one element of a group to another.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ScoreEntry {
+    pub source: ScoreSource,
+    pub element: String,
+    pub target: String,
+    pub points: i32,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct GroupScore {
+    pub score: i32,
+    pub entries: Vec<ScoreEntry>,
+}
+
+impl GroupScore {
+    /// Sums up the entries per source, sorted by source.
+    pub fn totals(&self) -> Vec<(ScoreSource, i32)> {
+        let mut totals: Vec<(ScoreSource, i32)> = Vec::new();
+        for entry in &self.entries {
+            match totals
+                .iter_mut()
+                .find(|(source, _)| *source == entry.source)
+            {
+                Some((_, points)) => *points += entry.points,
+                None => totals.push((entry.source, entry.points)),
+            }
+        }
+        totals.sort();
+        return totals;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::data::MatchingData;
+    use crate::matching::process;
+
+    fn entry(source: ScoreSource, points: i32) -> ScoreEntry {
+        return ScoreEntry {
+            source,
+            element: "01".to_string(),
+            target: "02".to_string(),
+            points,
+        };
+    }
+
+    #[test]
+    fn totals_per_source() {
+        let group = GroupScore {
+            score: 6,
+            entries: vec![
+                entry(ScoreSource::Rule(1), 2),
+                entry(ScoreSource::Base, 1),
+                entry(ScoreSource::Rule(1), 2),
+                entry(ScoreSource::Base, 1),
+            ],
+        };
+        assert_eq!(
+            group.totals(),
+            vec![(ScoreSource::Base, 2), (ScoreSource::Rule(1), 4)]
+        );
+    }
+
+    #[test]
+    fn breakdown_adds_up_to_the_score() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let result = process(&data).unwrap();
+        assert_eq!(result.groups.len(), result.connections.len());
+        for group in &result.groups {
+            let points: i32 = group.entries.iter().map(|entry| entry.points).sum();
+            assert_eq!(points, group.score);
+        }
+        assert_eq!(
+            result.groups.iter().map(|group| group.score).sum::<i32>(),
+            result.score
+        );
+        // the wish of Gabi for Tim (rule 3) is fulfilled and attributed to the rule
+        let group = result
+            .connections
+            .iter()
+            .position(|group| group.contains(&"02".to_string()))
+            .unwrap();
+        assert!(result.groups[group].entries.iter().any(|entry| {
+            entry.source == ScoreSource::Rule(2) && entry.element == "02" && entry.target == "01"
+        }));
+    }
+}
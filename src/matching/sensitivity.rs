@@ -0,0 +1,120 @@
+use crate::matching::data::MatchingData;
+use crate::matching::diff::moved;
+use crate::matching::grouping;
+use crate::matching::rules::RuleSeverity;
+use crate::matching::score::Scorer;
+use crate::matching::{prepare, process_with_settings, MatchingError, SolverSettings};
+use std::fmt;
+
+/// Change made to a single rule before solving again.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Perturbation {
+    Disabled,
+    Weight(i16), // replaces the score of the rule
+}
+
+impl fmt::Display for Perturbation {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Perturbation::Disabled => write!(f, "disabled"),
+            Perturbation::Weight(weight) => write!(f, "weight {}", weight),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Impact {
+    pub score: i32,          // score of the new result, measured with the changed rule
+    pub original_score: i32, // score of the new grouping, measured with the unchanged rules
+    pub score_delta: i32,    // original score compared to the unchanged result, 0 if nothing moved
+    pub moved: usize,        // elements that got different group members
+    pub feasible: bool,      // the new grouping satisfies the forced rules of the unchanged data
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct RuleImpact {
+    pub rule: usize,
+    pub perturbation: Perturbation,
+    pub outcome: Result<Impact, MatchingError>,
+}
+
+/// Solves once per rule with the rule disabled and once with its weight doubled, forced rules are only disabled.
+pub fn analyze(
+    matching_data: &MatchingData,
+    settings: &SolverSettings,
+) -> Result<Vec<RuleImpact>, MatchingError> {
+    let baseline = process_with_settings(matching_data, settings)?;
+    let (values, connections) = prepare(matching_data)?;
+    let mut impacts = Vec::new();
+    for (index, rule) in matching_data.rules.iter().enumerate() {
+        let mut perturbations = vec![Perturbation::Disabled];
+        match rule.severity {
+            RuleSeverity::Force | RuleSeverity::ForceExclude => {}
+            _ => perturbations.push(Perturbation::Weight(rule.get_score().saturating_mul(2))),
+        }
+        for perturbation in perturbations {
+            let mut changed = matching_data.clone();
+            match perturbation {
+                Perturbation::Disabled => {
+                    changed.rules.remove(index);
+                }
+                Perturbation::Weight(weight) => changed.rules[index].weight = Some(weight),
+            }
+            let outcome = process_with_settings(&changed, settings).map(|result| {
+                let original =
+                    grouping::verify(matching_data, &values, &connections, &result.connections);
+                Impact {
+                    score: result.score,
+                    original_score: original.score,
+                    score_delta: original.score - baseline.score,
+                    moved: moved(&baseline, &result).len(),
+                    feasible: original.violations.is_empty(),
+                }
+            });
+            impacts.push(RuleImpact {
+                rule: index,
+                perturbation,
+                outcome,
+            });
+        }
+    }
+    return Ok(impacts);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_rule_is_disabled_and_weighted() {
+        let data: MatchingData =
+            serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+        let impacts = analyze(&data, &SolverSettings::default()).unwrap();
+        assert_eq!(
+            impacts
+                .iter()
+                .map(|impact| (impact.rule, impact.perturbation))
+                .collect::<Vec<(usize, Perturbation)>>(),
+            vec![
+                (0, Perturbation::Disabled),
+                (0, Perturbation::Weight(-4)),
+                (1, Perturbation::Disabled),
+                (2, Perturbation::Disabled),
+                (2, Perturbation::Weight(4)),
+                (3, Perturbation::Disabled),
+            ]
+        );
+        for impact in &impacts {
+            let outcome = impact.outcome.as_ref().unwrap();
+            if impact.rule == 1 {
+                // without the forced location Bonn and Aachen get mixed
+                assert_eq!(outcome.moved, 4);
+                assert!(!outcome.feasible);
+            } else {
+                assert_eq!(outcome.moved, 0);
+                assert_eq!(outcome.score_delta, 0);
+                assert!(outcome.feasible);
+            }
+        }
+    }
+}
@@ -0,0 +1,413 @@
+use crate::matching::data::MatchingData;
+use crate::matching::rules::Rule;
+use crate::matching::schema::ElementValues;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+#[derive(Serialize, Deserialize, Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum IssueSeverity {
+    Error,   // processing is not possible
+    Warning, // processing works but probably not as intended
+    Info,
+}
+
+impl fmt::Display for IssueSeverity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct ValidationIssue {
+    pub severity: IssueSeverity,
+    pub message: String,
+}
+
+impl fmt::Display for ValidationIssue {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}: {}", self.severity, self.message)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Default)]
+pub struct ValidationReport {
+    pub issues: Vec<ValidationIssue>, // sorted by severity, errors first
+}
+
+impl ValidationReport {
+    pub fn has_errors(&self) -> bool {
+        return self
+            .issues
+            .iter()
+            .any(|issue| issue.severity == IssueSeverity::Error);
+    }
+
+    fn add(&mut self, severity: IssueSeverity, message: String) {
+        self.issues.push(ValidationIssue { severity, message });
+    }
+}
+
+impl fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for issue in &self.issues {
+            writeln!(f, "{}", issue)?;
+        }
+        Ok(())
+    }
+}
+
+impl MatchingData {
+    /// Checks the data for everything that would make processing fail or behave unexpectedly.
+    pub fn validate(&self) -> ValidationReport {
+        let mut report = ValidationReport::default();
+        self.validate_elements(&mut report);
+        self.validate_rules(&mut report);
+        self.validate_pairs(&mut report);
+        self.validate_layers(&mut report);
+        self.validate_outputs(&mut report);
+        report.issues.sort_by_key(|issue| issue.severity);
+        return report;
+    }
+
+    fn validate_elements(&self, report: &mut ValidationReport) {
+        if self.elements.is_empty() {
+            report.add(IssueSeverity::Error, "There are no elements".to_string());
+        }
+        let used_fields = self.used_fields();
+        let mut ids: Vec<&String> = self.elements.keys().collect();
+        ids.sort();
+        for id in &ids {
+            let element = &self.elements[*id];
+            let mut missing: Vec<&String> = self
+                .fields
+                .keys()
+                .filter(|field| !element.contains_key(*field))
+                .collect();
+            missing.sort();
+            for field in missing {
+                let severity = if used_fields.contains(field.as_str()) {
+                    IssueSeverity::Error
+                } else {
+                    IssueSeverity::Warning
+                };
+                report.add(
+                    severity,
+                    format!("Element {} has no value for {}", id, self.field_name(field)),
+                );
+            }
+            let mut unknown: Vec<&String> = element
+                .keys()
+                .filter(|field| !self.fields.contains_key(*field))
+                .collect();
+            unknown.sort();
+            for field in unknown {
+                report.add(
+                    IssueSeverity::Info,
+                    format!("Element {} has a value for the unknown field {}", id, field),
+                );
+            }
+        }
+        match ElementValues::parse(self) {
+            Ok(values) => {
+                for warning in values.warnings {
+                    report.add(IssueSeverity::Warning, warning.to_string());
+                }
+            }
+            Err(errors) => {
+                for error in errors {
+                    report.add(IssueSeverity::Error, error.to_string());
+                }
+            }
+        }
+        if let Some(name_field) = self.name_field() {
+            let mut names: HashMap<String, Vec<&String>> = HashMap::new();
+            for id in &ids {
+                if let Some(name) = self.elements[*id].get(name_field) {
+                    names.entry(name.to_string()).or_default().push(id);
+                }
+            }
+            let mut duplicates: Vec<(&String, &Vec<&String>)> =
+                names.iter().filter(|(_, ids)| ids.len() > 1).collect();
+            duplicates.sort();
+            for (name, ids) in duplicates {
+                report.add(
+                    IssueSeverity::Warning,
+                    format!(
+                        "The name '{}' is used by the elements {}",
+                        name,
+                        ids.iter()
+                            .map(|id| id.as_str())
+                            .collect::<Vec<&str>>()
+                            .join(", ")
+                    ),
+                );
+            }
+        }
+        let mut unknown_types: Vec<&String> = self
+            .schema
+            .keys()
+            .filter(|field| !self.fields.contains_key(*field))
+            .collect();
+        unknown_types.sort();
+        for field in unknown_types {
+            report.add(
+                IssueSeverity::Info,
+                format!("The type of the unknown field {} is ignored", field),
+            );
+        }
+    }
+
+    fn validate_rules(&self, report: &mut ValidationReport) {
+        for (index, rule) in self.rules.iter().enumerate() {
+            let number = index + 1;
+            if !rule.is_valid() {
+                report.add(
+                    IssueSeverity::Error,
+                    format!("Rule {} is incomplete", number),
+                );
+            }
+            for field in rule_fields(rule) {
+                if !field.is_empty() && !self.fields.contains_key(field) {
+                    report.add(
+                        IssueSeverity::Error,
+                        format!("Rule {} references the unknown field {}", number, field),
+                    );
+                }
+            }
+            for comparison in rule.all_comparisons() {
+                let field = comparison.field();
+                if !field.is_empty()
+                    && !self
+                        .field_type(field)
+                        .operands()
+                        .contains(&comparison.operand())
+                {
+                    report.add(
+                        IssueSeverity::Warning,
+                        format!(
+                            "Rule {} uses {} on the {} field {}, which is not meant for this type",
+                            number,
+                            comparison.operand(),
+                            self.field_type(field),
+                            self.field_name(field)
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn validate_pairs(&self, report: &mut ValidationReport) {
+        for (index, pair) in self.pairs.iter().enumerate() {
+            let number = index + 1;
+            if !pair.is_valid() {
+                report.add(
+                    IssueSeverity::Error,
+                    format!("Pair {} needs two different elements", number),
+                );
+                continue;
+            }
+            for id in [&pair.first, &pair.second] {
+                if !self.elements.contains_key(id) {
+                    report.add(
+                        IssueSeverity::Warning,
+                        format!(
+                            "Pair {} references the unknown element {} and is ignored",
+                            number, id
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn validate_layers(&self, report: &mut ValidationReport) {
+        for (index, layer) in self.layers.iter().enumerate() {
+            let mut unknown: Vec<&String> = Vec::new();
+            for entry in &layer.scores {
+                for id in [&entry.from, &entry.to] {
+                    if !self.elements.contains_key(id) && !unknown.contains(&id) {
+                        unknown.push(id);
+                    }
+                }
+            }
+            if !unknown.is_empty() {
+                report.add(
+                    IssueSeverity::Warning,
+                    format!(
+                        "Score layer {} ({}) references the unknown elements {}, their scores are ignored",
+                        index + 1,
+                        layer.name,
+                        unknown.iter().map(|id| id.as_str()).collect::<Vec<&str>>().join(", ")
+                    ),
+                );
+            }
+        }
+    }
+
+    fn validate_outputs(&self, report: &mut ValidationReport) {
+        if self.outputs.is_empty() {
+            report.add(IssueSeverity::Error, "There are no outputs".to_string());
+            return;
+        }
+        let mut sizes: Vec<&usize> = self.outputs.keys().collect();
+        sizes.sort();
+        let mut usable = 0;
+        for size in sizes {
+            if *size < 2 {
+                report.add(
+                    IssueSeverity::Warning,
+                    format!(
+                        "Output size {} is ignored, groups need at least 2 elements",
+                        size
+                    ),
+                );
+            } else if *size > self.elements.len() {
+                report.add(
+                    IssueSeverity::Warning,
+                    format!(
+                        "Output size {} is larger than the number of elements ({})",
+                        size,
+                        self.elements.len()
+                    ),
+                );
+            } else if self.outputs[size] == 0 {
+                report.add(
+                    IssueSeverity::Warning,
+                    format!("Output size {} may be used 0 times", size),
+                );
+            } else {
+                usable += 1;
+            }
+        }
+        if usable == 0 {
+            report.add(
+                IssueSeverity::Error,
+                "None of the outputs can be used".to_string(),
+            );
+        }
+        // ignored sizes don't hold any elements
+        let infinite = self
+            .outputs
+            .iter()
+            .any(|(size, amount)| *size >= 2 && *amount < 0);
+        let capacity: usize = self
+            .outputs
+            .iter()
+            .filter(|(size, amount)| **size >= 2 && **amount > 0)
+            .map(|(size, amount)| size * *amount as usize)
+            .sum();
+        if !infinite && capacity < self.elements.len() {
+            report.add(
+                IssueSeverity::Error,
+                format!(
+                    "The outputs can hold {} elements but there are {}",
+                    capacity,
+                    self.elements.len()
+                ),
+            );
+        }
+    }
+
+    /// Fields rules read from, elements without a value for one of them can't be processed.
+    fn used_fields(&self) -> HashSet<&str> {
+        return self.rules.iter().flat_map(rule_fields).collect();
+    }
+
+    fn field_name(&self, field: &str) -> String {
+        return match self.fields.get(field) {
+            Some(name) => format!("{} ({})", name, field),
+            None => field.to_string(),
+        };
+    }
+}
+
+fn rule_fields(rule: &Rule) -> Vec<&str> {
+    let mut fields: Vec<&str> = rule
+        .all_comparisons()
+        .into_iter()
+        .flat_map(|comparison| comparison.compared_fields())
+        .collect();
+    fields.extend(rule.scope.iter().map(|condition| condition.field.as_str()));
+    return fields;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::matching::pairs::PairRule;
+    use std::collections::BTreeMap;
+
+    fn concept() -> MatchingData {
+        return serde_json::from_str(include_str!("../../res/concept.json")).unwrap();
+    }
+
+    fn messages(report: &ValidationReport, severity: IssueSeverity) -> Vec<&str> {
+        return report
+            .issues
+            .iter()
+            .filter(|issue| issue.severity == severity)
+            .map(|issue| issue.message.as_str())
+            .collect();
+    }
+
+    #[test]
+    fn complete_data_has_no_errors() {
+        assert!(!concept().validate().has_errors());
+        let report = MatchingData::new().validate();
+        assert_eq!(
+            messages(&report, IssueSeverity::Error),
+            vec!["There are no elements", "There are no outputs"]
+        );
+    }
+
+    #[test]
+    fn missing_values_are_errors_only_for_fields_of_rules() {
+        let mut data = concept();
+        data.elements["01"].shift_remove("group3");
+        data.fields
+            .insert("group6".to_string(), "Hobby".to_string());
+        let report = data.validate();
+        assert_eq!(
+            messages(&report, IssueSeverity::Error),
+            vec!["Element 01 has no value for Standort (group3)"]
+        );
+        assert_eq!(
+            messages(&report, IssueSeverity::Warning).len(),
+            data.elements.len()
+        );
+        assert_eq!(
+            messages(&report, IssueSeverity::Warning)[0],
+            "Element 01 has no value for Hobby (group6)"
+        );
+    }
+
+    #[test]
+    fn rules_pairs_and_outputs() {
+        let mut data = concept();
+        data.rules[0].field = "group9".to_string();
+        let mut pair = PairRule::new();
+        pair.first = "01".to_string();
+        pair.second = "99".to_string();
+        data.pairs = vec![pair, PairRule::new()];
+        data.outputs = BTreeMap::from([(2, 1), (1, 2)]);
+        let report = data.validate();
+        assert_eq!(
+            messages(&report, IssueSeverity::Error),
+            vec![
+                "Rule 1 references the unknown field group9",
+                "Pair 2 needs two different elements",
+                "The outputs can hold 2 elements but there are 6",
+            ]
+        );
+        assert_eq!(
+            messages(&report, IssueSeverity::Warning),
+            vec![
+                "Pair 1 references the unknown element 99 and is ignored",
+                "Output size 1 is ignored, groups need at least 2 elements",
+            ]
+        );
+        assert_eq!(report.issues[0].severity, IssueSeverity::Error);
+    }
+}
@@ -0,0 +1,17 @@
+use crate::io::data_url;
+use wasm_bindgen::JsCast;
+use web_sys::HtmlAnchorElement;
+
+/// Saves a file built on demand by clicking a temporary link to it.
+pub fn download(name: &str, mime: &str, content: &[u8]) {
+    let document = match web_sys::window().and_then(|window| window.document()) {
+        Some(document) => document,
+        None => return,
+    };
+    if let Ok(element) = document.create_element("a") {
+        let link = element.unchecked_into::<HtmlAnchorElement>();
+        link.set_href(&data_url(mime, content));
+        link.set_download(name);
+        link.click();
+    }
+}
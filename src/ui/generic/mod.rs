@@ -1,2 +1,3 @@
 pub mod collapsable;
+pub mod download;
 pub mod table;
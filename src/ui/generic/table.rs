@@ -1,4 +1,5 @@
-use std::collections::HashMap;
+use indexmap::IndexMap;
+use std::fmt;
 use yew::prelude::*;
 
 pub struct TabledDisplay<HEADERS, DATA> {
@@ -16,6 +17,10 @@ where
     pub data: DATA,
     #[prop_or_default]
     pub children: Children,
+    #[prop_or_default]
+    pub move_header: Option<Callback<(String, isize)>>, // header key and offset, columns can be reordered if set
+    #[prop_or_default]
+    pub move_row: Option<Callback<(String, isize)>>, // row key and offset, rows can be reordered if set
 }
 
 impl Component for TabledDisplay<Vec<String>, Vec<Vec<String>>> {
@@ -52,12 +57,14 @@ impl Component for TabledDisplay<Vec<String>, Vec<Vec<String>>> {
     }
 }
 
-impl Component
-    for TabledDisplay<HashMap<String, String>, HashMap<String, HashMap<String, String>>>
+impl<VALUE> Component
+    for TabledDisplay<IndexMap<String, String>, IndexMap<String, IndexMap<String, VALUE>>>
+where
+    VALUE: fmt::Display + Clone + PartialEq + 'static,
 {
     type Message = ();
     type Properties =
-        TabledDisplayData<HashMap<String, String>, HashMap<String, HashMap<String, String>>>;
+        TabledDisplayData<IndexMap<String, String>, IndexMap<String, IndexMap<String, VALUE>>>;
 
     fn create(_ctx: &Context<Self>) -> Self {
         Self {
@@ -67,27 +74,32 @@ impl Component
     }
 
     fn view(&self, ctx: &Context<Self>) -> Html {
-        let mut keys = ctx
+        let keys = ctx
             .props()
             .headers
             .keys()
             .map(|key| key.to_string())
             .collect::<Vec<String>>();
-        keys.sort();
         return html! {
             <table>
                 <thead>
                     <th>{ "Id" }</th>
                     { keys.iter().map(|key| html! {
-                        <th>{ ctx.props().headers.get(key).expect(&*format!("Value missing for header {}", key)) }</th>
+                        <th>
+                            { self.view_move(&ctx.props().move_header, key, "fa fa-chevron-left", "fa fa-chevron-right") }
+                            { ctx.props().headers.get(key).unwrap_or_else(|| panic!("Value missing for header {}", key)) }
+                        </th>
                     }).collect::<Vec<Html>>() }
                 </thead>
                 <tbody>
                     { ctx.props().data.iter().map(|(data_key, data)| html! {
                         <tr>
-                            <td>{ data_key }</td>
+                            <td>
+                                { self.view_move(&ctx.props().move_row, data_key, "fa fa-chevron-up", "fa fa-chevron-down") }
+                                { data_key }
+                            </td>
                             { keys.iter().map(|key| html! {
-                                <td>{ data.get(key).expect(&*format!("Value missing for {} {}", data_key, key)) }</td>
+                                <td>{ data.get(key).map(|value| value.to_string()).unwrap_or_default() }</td>
                             }).collect::<Vec<Html>>() }
                         </tr>
                     }).collect::<Vec<Html>>() }
@@ -97,3 +109,26 @@ impl Component
         };
     }
 }
+
+impl<HEADERS, DATA> TabledDisplay<HEADERS, DATA> {
+    /// Buttons moving a column or row one place back or forth, nothing if it can't be moved.
+    fn view_move(
+        &self,
+        callback: &Option<Callback<(String, isize)>>,
+        key: &str,
+        back_icon: &str,
+        forth_icon: &str,
+    ) -> Html {
+        let callback = match callback {
+            Some(callback) => callback,
+            None => return html! {},
+        };
+        let (back, forth) = (key.to_string(), key.to_string());
+        return html! {
+            <span class="move">
+                <button onclick={callback.reform(move |_| (back.clone(), -1))}><i class={ back_icon.to_string() }/></button>
+                <button onclick={callback.reform(move |_| (forth.clone(), 1))}><i class={ forth_icon.to_string() }/></button>
+            </span>
+        };
+    }
+}
@@ -0,0 +1,332 @@
+use crate::io::csv::{next_field_id, read_table, to_elements, ColumnMapping, CsvOptions, Table};
+use crate::io::spreadsheet::{is_spreadsheet, read_sheet, sheet_names};
+use crate::ui::ui::{BaseMsg, MatchingDataUpdate};
+use gloo_file::callbacks::FileReader;
+use gloo_file::File;
+use indexmap::IndexMap;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+const PREVIEW_ROWS: usize = 5;
+
+pub(crate) struct CsvLoader {
+    text: String,
+    options: CsvOptions,
+    workbook: Option<Vec<u8>>, // spreadsheet file, used instead of the text if set
+    sheets: Vec<String>,
+    sheet: Option<String>, // first sheet if not set
+    table: Option<Table>,
+    mapping: ColumnMapping,
+    error: Option<String>,
+    reader: Option<FileReader>, // kept until the selected file has been read
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct CsvLoaderProps {
+    #[prop_or_default]
+    pub change_callback: Option<Callback<BaseMsg>>,
+    pub fields: IndexMap<String, String>, // existing fields columns can be mapped to
+}
+
+pub enum CsvMsg {
+    UpdateText(String),
+    SelectFile(Option<File>),
+    FileRead(Result<String, String>),
+    WorkbookRead(Result<Vec<u8>, String>),
+    Sheet(String),
+    Delimiter(Option<u8>),
+    Quote(Option<u8>),
+    Headers(bool),
+    IdColumn(Option<usize>),
+    ColumnField(usize, String), // column, field id, "" to skip or "new" for a new field
+    Import,
+}
+
+impl Component for CsvLoader {
+    type Message = CsvMsg;
+    type Properties = CsvLoaderProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            text: "".to_string(),
+            workbook: None,
+            sheets: Vec::new(),
+            sheet: None,
+            options: CsvOptions::default(),
+            table: None,
+            mapping: ColumnMapping {
+                id_column: None,
+                fields: Vec::new(),
+            },
+            error: None,
+            reader: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            CsvMsg::UpdateText(text) => {
+                self.text = text;
+                self.workbook = None;
+                self.read(ctx);
+            }
+            CsvMsg::SelectFile(file) => {
+                if let Some(file) = file {
+                    let link = ctx.link().clone();
+                    self.reader = Some(if is_spreadsheet(&file.name()) {
+                        gloo_file::callbacks::read_as_bytes(&file, move |result| {
+                            link.send_message(CsvMsg::WorkbookRead(
+                                result.map_err(|error| error.to_string()),
+                            ))
+                        })
+                    } else {
+                        gloo_file::callbacks::read_as_text(&file, move |result| {
+                            link.send_message(CsvMsg::FileRead(
+                                result.map_err(|error| error.to_string()),
+                            ))
+                        })
+                    });
+                }
+                return false;
+            }
+            CsvMsg::FileRead(result) => {
+                self.reader = None;
+                match result {
+                    Ok(text) => {
+                        self.text = text;
+                        self.workbook = None;
+                        self.read(ctx);
+                    }
+                    Err(error) => self.error = Some(format!("File could not be read: {}", error)),
+                }
+            }
+            CsvMsg::WorkbookRead(result) => {
+                self.reader = None;
+                match result.and_then(|bytes| Ok((sheet_names(&bytes)?, bytes))) {
+                    Ok((sheets, bytes)) => {
+                        self.sheets = sheets;
+                        self.sheet = None;
+                        self.workbook = Some(bytes);
+                        self.read(ctx);
+                    }
+                    Err(error) => self.error = Some(format!("File could not be read: {}", error)),
+                }
+            }
+            CsvMsg::Sheet(sheet) => {
+                self.sheet = Some(sheet);
+                self.read(ctx);
+            }
+            CsvMsg::Delimiter(delimiter) => {
+                self.options.delimiter = delimiter;
+                self.read(ctx);
+            }
+            CsvMsg::Quote(quote) => {
+                self.options.quote = quote;
+                self.read(ctx);
+            }
+            CsvMsg::Headers(has_headers) => {
+                self.options.has_headers = has_headers;
+                self.read(ctx);
+            }
+            CsvMsg::IdColumn(column) => self.mapping.id_column = column,
+            CsvMsg::ColumnField(column, field) => {
+                self.mapping.fields[column] = match &*field {
+                    "" => None,
+                    "new" => Some(next_field_id(&self.mapped_fields(ctx))),
+                    _ => Some(field),
+                }
+            }
+            CsvMsg::Import => {
+                let table = match &self.table {
+                    Some(table) => table,
+                    None => return false,
+                };
+                match to_elements(table, &self.mapping, &ctx.props().fields) {
+                    Ok((fields, elements)) => {
+                        self.error = None;
+                        if let Some(callback) = &ctx.props().change_callback {
+                            callback.emit(BaseMsg::UpdateMatchingData(Box::new(
+                                MatchingDataUpdate {
+                                    fields: Some(fields),
+                                    elements: Some(elements),
+                                    ..MatchingDataUpdate::default()
+                                },
+                            )));
+                        }
+                    }
+                    Err(error) => self.error = Some(error),
+                }
+            }
+        }
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <div class="flex-vertical">
+                <input
+                    type="file"
+                    accept=".csv,.tsv,.txt,.xlsx,.xlsm,.xls,.ods"
+                    onchange={ctx.link().callback(|e: Event| {
+                        let files = e.target_unchecked_into::<HtmlInputElement>().files();
+                        CsvMsg::SelectFile(files.and_then(|files| files.get(0)).map(File::from))
+                    })}
+                />
+                <textarea
+                    class="edit"
+                    rows="6"
+                    placeholder="Paste CSV here"
+                    value={self.text.clone()}
+                    oninput={ctx.link().callback(|e: InputEvent| CsvMsg::UpdateText(e.target_unchecked_into::<HtmlTextAreaElement>().value()))}
+                />
+                <div class="flex-horizontal">
+                    { self.view_format(ctx) }
+                    <label>
+                        <input
+                            type="checkbox"
+                            checked={self.options.has_headers}
+                            onchange={ctx.link().callback(|e: Event| CsvMsg::Headers(e.target_unchecked_into::<HtmlInputElement>().checked()))}
+                        />
+                        { "Header row" }
+                    </label>
+                </div>
+                { self.view_preview(ctx) }
+                {
+                    match &self.error {
+                        Some(error) => html! { <pre class="invalid">{ error }</pre> },
+                        None => html! {},
+                    }
+                }
+            </div>
+        };
+    }
+}
+
+impl CsvLoader {
+    fn read(&mut self, ctx: &Context<Self>) {
+        self.error = None;
+        let table = match &self.workbook {
+            Some(bytes) => read_sheet(bytes, self.sheet.as_deref(), self.options.has_headers),
+            None => read_table(&self.text, &self.options),
+        };
+        self.table = match table {
+            Ok(table) => {
+                self.mapping = ColumnMapping::guess(&table, &ctx.props().fields);
+                Some(table)
+            }
+            Err(error) => {
+                self.error = Some(error);
+                None
+            }
+        };
+    }
+
+    /// Existing fields plus the new ones columns are currently mapped to.
+    fn mapped_fields(&self, ctx: &Context<Self>) -> IndexMap<String, String> {
+        let mut fields = ctx.props().fields.clone();
+        if let Some(table) = &self.table {
+            for (column, field) in self.mapping.fields.iter().enumerate() {
+                if let Some(field) = field {
+                    fields
+                        .entry(field.clone())
+                        .or_insert(table.headers[column].clone());
+                }
+            }
+        }
+        return fields;
+    }
+
+    /// Sheet of a spreadsheet, or how the CSV text is split into cells.
+    fn view_format(&self, ctx: &Context<Self>) -> Html {
+        if self.workbook.is_some() {
+            return html! {
+                <>
+                    { "Sheet " }
+                    <select onchange={ctx.link().callback(|e: Event| CsvMsg::Sheet(e.target_unchecked_into::<HtmlSelectElement>().value()))}>
+                        { self.sheets.iter().enumerate().map(|(index, sheet)| html! {
+                            <option value={ sheet.clone() } selected={ self.sheet.as_ref().map_or(index == 0, |selected| selected == sheet) }>{ sheet }</option>
+                        }).collect::<Vec<Html>>() }
+                    </select>
+                </>
+            };
+        }
+        return html! {
+            <>
+                { "Delimiter " }
+                { self.view_char_select(ctx, self.options.delimiter, &[(b',', ","), (b';', ";"), (b'\t', "Tab"), (b'|', "|")], CsvMsg::Delimiter) }
+                { " Quote " }
+                { self.view_char_select(ctx, self.options.quote, &[(b'"', "\""), (b'\'', "'")], CsvMsg::Quote) }
+            </>
+        };
+    }
+
+    fn view_char_select<F>(
+        &self,
+        ctx: &Context<Self>,
+        selected: Option<u8>,
+        choices: &[(u8, &str)],
+        to_msg: F,
+    ) -> Html
+    where
+        F: Fn(Option<u8>) -> CsvMsg + 'static,
+    {
+        return html! {
+            <select onchange={ctx.link().callback(move |e: Event| to_msg(e.target_unchecked_into::<HtmlSelectElement>().value().parse::<u8>().ok()))}>
+                <option value="" selected={ selected.is_none() }>{ "Auto" }</option>
+                { choices.iter().map(|(value, name)| html! {
+                    <option value={ value.to_string() } selected={ selected == Some(*value) }>{ name }</option>
+                }).collect::<Vec<Html>>() }
+            </select>
+        };
+    }
+
+    fn view_preview(&self, ctx: &Context<Self>) -> Html {
+        let table = match &self.table {
+            Some(table) if !table.headers.is_empty() => table,
+            _ => return html! {},
+        };
+        let fields = self.mapped_fields(ctx);
+        let field_ids: Vec<(&String, &String)> = fields.iter().collect();
+        return html! {
+            <>
+                <div>
+                    { "Id column " }
+                    <select onchange={ctx.link().callback(|e: Event| CsvMsg::IdColumn(e.target_unchecked_into::<HtmlSelectElement>().value().parse::<usize>().ok()))}>
+                        <option value="" selected={ self.mapping.id_column.is_none() }>{ "Generate" }</option>
+                        { table.headers.iter().enumerate().map(|(column, header)| html! {
+                            <option value={ column.to_string() } selected={ self.mapping.id_column == Some(column) }>{ header }</option>
+                        }).collect::<Vec<Html>>() }
+                    </select>
+                </div>
+                <table>
+                    <thead>
+                        { table.headers.iter().enumerate().map(|(column, header)| html! {
+                            <th>
+                                <div>{ header }</div>
+                                <select onchange={ctx.link().callback(move |e: Event| CsvMsg::ColumnField(column, e.target_unchecked_into::<HtmlSelectElement>().value()))}>
+                                    <option value="" selected={ self.mapping.fields[column].is_none() }>{ "Skip" }</option>
+                                    <option value="new">{ "New field" }</option>
+                                    { field_ids.iter().map(|(id, name)| html! {
+                                        <option value={ (*id).clone() } selected={ self.mapping.fields[column].as_ref() == Some(*id) }>
+                                            { format!("{} ({})", name, id) }
+                                        </option>
+                                    }).collect::<Vec<Html>>() }
+                                </select>
+                            </th>
+                        }).collect::<Vec<Html>>() }
+                    </thead>
+                    <tbody>
+                        { table.rows.iter().take(PREVIEW_ROWS).map(|row| html! {
+                            <tr>
+                                { row.iter().map(|cell| html! { <td>{ cell }</td> }).collect::<Vec<Html>>() }
+                            </tr>
+                        }).collect::<Vec<Html>>() }
+                    </tbody>
+                </table>
+                <button onclick={ctx.link().callback(|_| CsvMsg::Import)}>
+                    { format!("Import {} elements", table.rows.len()) }
+                </button>
+            </>
+        };
+    }
+}
@@ -0,0 +1,72 @@
+use crate::matching::grouping::parse_grouping;
+use crate::ui::ui::BaseMsg;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+pub(crate) struct GroupingLoader {
+    text: String,
+    error: Option<String>,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct GroupingLoaderProps {
+    #[prop_or_default]
+    pub change_callback: Option<Callback<BaseMsg>>,
+}
+
+pub enum GroupingMsg {
+    UpdateText(String),
+    Verify,
+}
+
+impl Component for GroupingLoader {
+    type Message = GroupingMsg;
+    type Properties = GroupingLoaderProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            text: "".to_string(),
+            error: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            GroupingMsg::UpdateText(text) => {
+                self.text = text;
+                return false;
+            }
+            GroupingMsg::Verify => match parse_grouping(&self.text) {
+                Ok(groups) => {
+                    self.error = None;
+                    if let Some(callback) = &ctx.props().change_callback {
+                        callback.emit(BaseMsg::VerifyGrouping(groups));
+                    }
+                }
+                Err(error) => self.error = Some(error),
+            },
+        }
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <div class="flex-vertical">
+                <textarea
+                    class="edit"
+                    rows="6"
+                    placeholder="One group of element ids per line, e.g. 01, 04, 07"
+                    value={self.text.clone()}
+                    oninput={ctx.link().callback(|e: InputEvent| GroupingMsg::UpdateText(e.target_unchecked_into::<HtmlTextAreaElement>().value()))}
+                />
+                <button onclick={ctx.link().callback(|_| GroupingMsg::Verify)}>{ "Verify grouping" }</button>
+                {
+                    match &self.error {
+                        Some(error) => html! { <pre class="invalid">{ error }</pre> },
+                        None => html! {},
+                    }
+                }
+            </div>
+        };
+    }
+}
@@ -1,2 +1,5 @@
 pub mod csv_loader;
-pub mod json_loader;
+pub mod grouping_loader;
+pub mod project_loader;
+pub mod schema_editor;
+pub mod score_loader;
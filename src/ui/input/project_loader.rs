@@ -0,0 +1,174 @@
+use crate::io::project::{read_project, write_project, Metadata, Project, ProjectFormat};
+use crate::matching::data::MatchingData;
+use crate::matching::history::RunRecord;
+
+use crate::ui::generic::download::download;
+use crate::ui::ui::{now, BaseMsg, MatchingDataUpdate};
+use web_sys::{HtmlSelectElement, HtmlTextAreaElement};
+use yew::events::{Event, InputEvent, KeyboardEvent};
+use yew::{html, Callback, Component, Context, Html, Properties, TargetCast};
+
+pub(crate) struct ProjectLoader {
+    text: String,
+    format: Option<ProjectFormat>, // detected from the text if not set
+    save_format: ProjectFormat,
+    errors: Vec<String>,
+    notices: Vec<String>, // migrations applied to the last loaded project
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ProjectProps {
+    #[prop_or_default]
+    pub change_callback: Option<Callback<BaseMsg>>,
+    #[prop_or_default]
+    pub matching_data: Option<MatchingData>, // offered for download if set
+    #[prop_or_default]
+    pub history: Vec<RunRecord>, // results saved along with the data
+}
+
+pub enum ProjectMsg {
+    UpdateText(String),
+    Format(Option<ProjectFormat>),
+    SaveFormat(ProjectFormat),
+    Load,
+    Save,
+}
+
+impl Component for ProjectLoader {
+    type Message = ProjectMsg;
+    type Properties = ProjectProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            text: "".to_string(),
+            format: None,
+            save_format: ProjectFormat::Json,
+            errors: Vec::new(),
+            notices: Vec::new(),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ProjectMsg::UpdateText(text) => {
+                self.text = text;
+                return false;
+            }
+            ProjectMsg::Format(format) => self.format = format,
+            ProjectMsg::SaveFormat(format) => self.save_format = format,
+            ProjectMsg::Load => {
+                if let Some(callback) = &ctx.props().change_callback {
+                    let (project, notices) = match read_project(&self.text, self.format) {
+                        Ok(loaded) => loaded,
+                        Err(error) => {
+                            self.errors = vec![error];
+                            self.notices.clear();
+                            return true;
+                        }
+                    };
+                    self.errors.clear(); // remaining problems are listed by the validation report
+                    self.notices = notices;
+                    let data = project.data;
+                    callback.emit(BaseMsg::UpdateMatchingData(Box::new(MatchingDataUpdate {
+                        fields: Some(data.fields),
+                        elements: Some(data.elements),
+                        rules: Some(data.rules),
+                        outputs: Some(data.outputs),
+                        pairs: Some(data.pairs),
+                        schema: Some(data.schema),
+                        layers: Some(data.layers),
+                        name_field: Some(data.name_field),
+                    })));
+                    callback.emit(BaseMsg::LoadHistory(project.history));
+                }
+            }
+            ProjectMsg::Save => {
+                if let Some(matching_data) = &ctx.props().matching_data {
+                    let project = Project {
+                        data: matching_data.clone(),
+                        history: ctx.props().history.clone(),
+                        metadata: Some(Metadata::new(now())),
+                    };
+                    match write_project(&project, self.save_format) {
+                        Ok(text) => download(
+                            &format!("project.{}", self.save_format.extension()),
+                            self.save_format.mime(),
+                            text.as_bytes(),
+                        ),
+                        Err(error) => self.errors = vec![error],
+                    }
+                }
+            }
+        }
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <div class="flex-vertical">
+                <textarea
+                    id="projectTextarea"
+                    class="edit"
+                    rows="10"
+                    placeholder="Paste a project as JSON, YAML or TOML"
+                    value={self.text.clone()}
+                    oninput={ctx.link().callback(|e: InputEvent| ProjectMsg::UpdateText(e.target_unchecked_into::<HtmlTextAreaElement>().value()))}
+                    onkeypress={ctx.link().batch_callback(move |e: KeyboardEvent| {
+                        if e.key() == "Enter" && e.ctrl_key() { Some(ProjectMsg::Load) } else { None }
+                    })}
+                />
+                <div class="flex-horizontal">
+                    <select onchange={ctx.link().callback(|e: Event| ProjectMsg::Format(parse_format(&e.target_unchecked_into::<HtmlSelectElement>().value())))}>
+                        <option value="" selected={ self.format.is_none() }>{ "Detect format" }</option>
+                        { self.view_format_options(self.format) }
+                    </select>
+                    <button onclick={ctx.link().callback(|_| ProjectMsg::Load)}>{ "Load" }</button>
+                </div>
+                { self.view_save(ctx) }
+                <ul class="invalid">
+                    { self.errors.iter().map(|error| html! { <li>{ error }</li> }).collect::<Vec<Html>>() }
+                </ul>
+                <ul class="validation">
+                    { self.notices.iter().map(|notice| html! { <li class="info">{ notice }</li> }).collect::<Vec<Html>>() }
+                </ul>
+            </div>
+        };
+    }
+}
+
+impl ProjectLoader {
+    fn view_format_options(&self, selected: Option<ProjectFormat>) -> Html {
+        return ProjectFormat::values()
+            .into_iter()
+            .map(|format| {
+                html! {
+                    <option value={ format.to_string() } selected={ selected == Some(format) }>{ format.to_string() }</option>
+                }
+            })
+            .collect::<Html>();
+    }
+
+    /// The project is only written when it is saved, the data can be too large to do it on every render.
+    fn view_save(&self, ctx: &Context<Self>) -> Html {
+        if ctx.props().matching_data.is_none() {
+            return html! {};
+        }
+        return html! {
+            <div class="flex-horizontal">
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let format = parse_format(&e.target_unchecked_into::<HtmlSelectElement>().value());
+                    ProjectMsg::SaveFormat(format.unwrap_or(ProjectFormat::Json))
+                })}>
+                    { self.view_format_options(Some(self.save_format)) }
+                </select>
+                <button onclick={ctx.link().callback(|_| ProjectMsg::Save)}>{ "Save project" }</button>
+            </div>
+        };
+    }
+}
+
+fn parse_format(value: &str) -> Option<ProjectFormat> {
+    return ProjectFormat::values()
+        .into_iter()
+        .find(|format| format.to_string() == value);
+}
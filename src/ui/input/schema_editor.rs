@@ -0,0 +1,118 @@
+use crate::matching::schema::FieldType;
+use crate::ui::generic::table::TabledDisplay;
+use crate::ui::ui::{BaseMsg, MatchingDataUpdate};
+use indexmap::IndexMap;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+pub(crate) struct SchemaEditor {
+    headers: Vec<String>,
+}
+
+pub enum SchemaMsg {
+    ChangeType(String, String),
+    NameField(String),
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct SchemaEditorProps {
+    #[prop_or_default]
+    pub change_callback: Option<Callback<BaseMsg>>,
+    pub fields: IndexMap<String, String>,
+    pub schema: IndexMap<String, FieldType>,
+    #[prop_or_default]
+    pub name_field: Option<String>, // used to resolve references by name
+}
+
+impl Component for SchemaEditor {
+    type Message = SchemaMsg;
+    type Properties = SchemaEditorProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            headers: Vec::from([
+                "Field".to_string(),
+                "Name".into(),
+                "Type".into(),
+                "Element names".into(),
+            ]),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let mut schema = ctx.props().schema.clone();
+        match msg {
+            SchemaMsg::ChangeType(field, name) => {
+                match FieldType::values()
+                    .into_iter()
+                    .find(|field_type| field_type.to_string() == name)
+                {
+                    Some(FieldType::Text) => {
+                        schema.shift_remove(&field);
+                    }
+                    Some(field_type) => {
+                        schema.insert(field, field_type);
+                    }
+                    None => return false,
+                }
+            }
+            SchemaMsg::NameField(field) => {
+                if let Some(callback) = &ctx.props().change_callback {
+                    callback.emit(BaseMsg::SetNameField(field));
+                }
+                return false;
+            }
+        };
+        if let Some(callback) = &ctx.props().change_callback {
+            callback.emit(BaseMsg::UpdateMatchingData(Box::new(MatchingDataUpdate {
+                schema: Some(schema),
+                ..MatchingDataUpdate::default()
+            })));
+        }
+        false // redraw triggered by parent
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let fields: Vec<(&String, &String)> = ctx.props().fields.iter().collect();
+        return html! {
+            <TabledDisplay<std::vec::Vec<String>, std::vec::Vec<std::vec::Vec<String>>>
+                headers={self.headers.clone()}
+                data={Vec::new()}
+            >
+                {
+                    fields.iter().map(|(id, name)| {
+                        let field = (*id).clone();
+                        let name_field = (*id).clone();
+                        let field_type = *ctx.props().schema.get(*id).unwrap_or(&FieldType::Text);
+                        html! {
+                            <tr>
+                                <td>{ id }</td>
+                                <td>{ name }</td>
+                                <td>
+                                    <select onchange={ctx.link().callback(move |e: Event| SchemaMsg::ChangeType(field.clone(), e.target_unchecked_into::<HtmlSelectElement>().value()))}>
+                                      { FieldType::values().iter().map(|value| html! {
+                                        <option
+                                            value={ value.to_string() }
+                                            selected={ field_type == *value }
+                                        >
+                                            { value.to_string() }
+                                        </option>
+                                      }).collect::<Vec<Html>>() }
+                                    </select>
+                                </td>
+                                <td>
+                                    <input
+                                        type="radio"
+                                        name="name-field"
+                                        checked={ ctx.props().name_field.as_ref() == Some(*id) }
+                                        onchange={ctx.link().callback(move |_| SchemaMsg::NameField(name_field.clone()))}
+                                    />
+                                </td>
+                            </tr>
+                        }
+                    }).collect::<Vec<Html>>()
+                }
+            </TabledDisplay<std::vec::Vec<String>, std::vec::Vec<std::vec::Vec<String>>>>
+        };
+    }
+}
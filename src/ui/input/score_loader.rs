@@ -0,0 +1,248 @@
+use crate::io::csv::{read_table, CsvOptions};
+use crate::io::scores::{read_scores, ScoreFormat, ScoreImport, ScoreOptions};
+use crate::matching::layers::ScoreLayer;
+use crate::ui::ui::{BaseMsg, MatchingDataUpdate};
+use gloo_file::callbacks::FileReader;
+use gloo_file::File;
+use indexmap::IndexMap;
+use web_sys::{HtmlInputElement, HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+pub(crate) struct ScoreLoader {
+    text: String,
+    name: String, // name of the new layer, the file name if one was selected
+    csv: CsvOptions,
+    options: ScoreOptions,
+    import: Option<Result<ScoreImport, String>>, // scores read from the current text
+    reader: Option<FileReader>,                  // kept until the selected file has been read
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ScoreLoaderProps {
+    #[prop_or_default]
+    pub change_callback: Option<Callback<BaseMsg>>,
+    pub layers: Vec<ScoreLayer>,
+    pub labels: IndexMap<String, String>, // element id -> label, in row order
+}
+
+pub enum ScoreMsg {
+    UpdateText(String),
+    SelectFile(Option<File>),
+    FileRead(String, Result<String, String>), // file name and content
+    Name(String),
+    Format(Option<ScoreFormat>),
+    Headers(bool),
+    Symmetric(bool),
+    Factor(f64),
+    Import,
+    Delete(usize),
+}
+
+impl Component for ScoreLoader {
+    type Message = ScoreMsg;
+    type Properties = ScoreLoaderProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            text: "".to_string(),
+            name: "".to_string(),
+            csv: CsvOptions::default(),
+            options: ScoreOptions::default(),
+            import: None,
+            reader: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ScoreMsg::UpdateText(text) => self.text = text,
+            ScoreMsg::SelectFile(file) => {
+                if let Some(file) = file {
+                    let link = ctx.link().clone();
+                    let name = file.name();
+                    self.reader = Some(gloo_file::callbacks::read_as_text(&file, move |result| {
+                        link.send_message(ScoreMsg::FileRead(
+                            name,
+                            result.map_err(|error| error.to_string()),
+                        ))
+                    }));
+                }
+                return false;
+            }
+            ScoreMsg::FileRead(name, result) => {
+                self.reader = None;
+                match result {
+                    Ok(text) => {
+                        self.text = text;
+                        self.name = name;
+                    }
+                    Err(error) => {
+                        self.import = Some(Err(format!("File could not be read: {}", error)));
+                        return true;
+                    }
+                }
+            }
+            ScoreMsg::Name(name) => {
+                self.name = name;
+                return false;
+            }
+            ScoreMsg::Format(format) => self.options.format = format,
+            ScoreMsg::Headers(has_headers) => self.csv.has_headers = has_headers,
+            ScoreMsg::Symmetric(symmetric) => self.options.symmetric = symmetric,
+            ScoreMsg::Factor(factor) => self.options.factor = factor,
+            ScoreMsg::Import => {
+                let scores = match &self.import {
+                    Some(Ok(import)) => import.scores.clone(),
+                    _ => return false,
+                };
+                let mut layers = ctx.props().layers.clone();
+                let name = match self.name.trim() {
+                    "" => format!("Layer {}", layers.len() + 1),
+                    name => name.to_string(),
+                };
+                layers.push(ScoreLayer::new(name, scores));
+                self.emit(ctx, layers);
+                self.text.clear();
+                self.name.clear();
+            }
+            ScoreMsg::Delete(index) => {
+                let mut layers = ctx.props().layers.clone();
+                layers.remove(index);
+                self.emit(ctx, layers);
+                return false; // redraw triggered by parent
+            }
+        }
+        self.read(ctx);
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <div class="flex-vertical">
+                <ul>
+                    { ctx.props().layers.iter().enumerate().map(|(index, layer)| html! {
+                        <li>
+                            <button onclick={ctx.link().callback(move |_| ScoreMsg::Delete(index))}>{ "-" }</button>
+                            { format!(" {}: {} scores", layer.name, layer.scores.len()) }
+                        </li>
+                    }).collect::<Vec<Html>>() }
+                </ul>
+                <input
+                    type="file"
+                    accept=".csv,.tsv,.txt"
+                    onchange={ctx.link().callback(|e: Event| {
+                        let files = e.target_unchecked_into::<HtmlInputElement>().files();
+                        ScoreMsg::SelectFile(files.and_then(|files| files.get(0)).map(File::from))
+                    })}
+                />
+                <textarea
+                    class="edit"
+                    rows="6"
+                    placeholder="Paste an edge list (element, other element, score) or a matrix with element ids as first row and column"
+                    value={self.text.clone()}
+                    oninput={ctx.link().callback(|e: InputEvent| ScoreMsg::UpdateText(e.target_unchecked_into::<HtmlTextAreaElement>().value()))}
+                />
+                <div class="flex-horizontal">
+                    <select onchange={ctx.link().callback(|e: Event| {
+                        let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+                        ScoreMsg::Format(ScoreFormat::values().into_iter().find(|format| format.to_string() == value))
+                    })}>
+                        <option value="" selected={ self.options.format.is_none() }>{ "Detect" }</option>
+                        { ScoreFormat::values().iter().map(|format| html! {
+                            <option value={ format.to_string() } selected={ self.options.format == Some(*format) }>
+                                { match format {
+                                    ScoreFormat::EdgeList => "Edge list",
+                                    ScoreFormat::Matrix => "Matrix",
+                                } }
+                            </option>
+                        }).collect::<Vec<Html>>() }
+                    </select>
+                    { self.view_checkbox(ctx, "Header row", self.csv.has_headers, ScoreMsg::Headers) }
+                    { self.view_checkbox(ctx, "Both directions", self.options.symmetric, ScoreMsg::Symmetric) }
+                    <label>
+                        { "Factor " }
+                        <input
+                            type="number"
+                            step="any"
+                            value={ self.options.factor.to_string() }
+                            onchange={ctx.link().callback(|e: Event| ScoreMsg::Factor(e.target_unchecked_into::<HtmlInputElement>().value().parse::<f64>().unwrap_or(1.0)))}
+                        />
+                    </label>
+                    <input
+                        type="text"
+                        placeholder="Layer name"
+                        value={ self.name.clone() }
+                        onchange={ctx.link().callback(|e: Event| ScoreMsg::Name(e.target_unchecked_into::<HtmlInputElement>().value()))}
+                    />
+                </div>
+                { self.view_import(ctx) }
+            </div>
+        };
+    }
+}
+
+impl ScoreLoader {
+    fn read(&mut self, ctx: &Context<Self>) {
+        self.import = if self.text.trim().is_empty() {
+            None
+        } else {
+            Some(
+                read_table(&self.text, &self.csv)
+                    .and_then(|table| read_scores(&table, &ctx.props().labels, &self.options)),
+            )
+        };
+    }
+
+    fn emit(&self, ctx: &Context<Self>, layers: Vec<ScoreLayer>) {
+        if let Some(callback) = &ctx.props().change_callback {
+            callback.emit(BaseMsg::UpdateMatchingData(Box::new(MatchingDataUpdate {
+                layers: Some(layers),
+                ..MatchingDataUpdate::default()
+            })));
+        }
+    }
+
+    /// Number of scores that would be imported and the ids that were not found.
+    fn view_import(&self, ctx: &Context<Self>) -> Html {
+        return match &self.import {
+            Some(Ok(import)) => html! {
+                <>
+                    {
+                        if import.unknown.is_empty() {
+                            html! {}
+                        } else {
+                            html! {
+                                <ul class="validation">
+                                    <li class="warning">
+                                        { format!("Unknown elements, their scores are skipped: {}", import.unknown.join(", ")) }
+                                    </li>
+                                </ul>
+                            }
+                        }
+                    }
+                    <button onclick={ctx.link().callback(|_| ScoreMsg::Import)} disabled={ import.scores.is_empty() }>
+                        { format!("Import {} scores", import.scores.len()) }
+                    </button>
+                </>
+            },
+            Some(Err(error)) => html! { <pre class="invalid">{ error }</pre> },
+            None => html! {},
+        };
+    }
+
+    fn view_checkbox<F>(&self, ctx: &Context<Self>, label: &str, checked: bool, to_msg: F) -> Html
+    where
+        F: Fn(bool) -> ScoreMsg + 'static,
+    {
+        return html! {
+            <label>
+                <input
+                    type="checkbox"
+                    checked={ checked }
+                    onchange={ctx.link().callback(move |e: Event| to_msg(e.target_unchecked_into::<HtmlInputElement>().checked()))}
+                />
+                { label }
+            </label>
+        };
+    }
+}
@@ -1,4 +1,5 @@
 pub mod generic;
 pub mod input;
+pub mod results;
 pub mod rules;
 pub mod ui;
@@ -0,0 +1,142 @@
+use crate::matching::data::MatchingResult;
+use crate::matching::diff::{diff, Sources};
+use std::collections::HashMap;
+use web_sys::HtmlSelectElement;
+use yew::prelude::*;
+
+pub(crate) struct ResultComparison {
+    first: usize,
+    second: usize,
+}
+
+pub enum ComparisonMsg {
+    First(usize),
+    Second(usize),
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ResultComparisonProps {
+    pub results: Vec<(usize, MatchingResult, Sources)>, // number of the result -> result, its rules and pairs
+    pub labels: HashMap<String, String>,                // element id -> label
+}
+
+impl Component for ResultComparison {
+    type Message = ComparisonMsg;
+    type Properties = ResultComparisonProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        let count = ctx.props().results.len();
+        Self {
+            first: count.saturating_sub(2),
+            second: count.saturating_sub(1),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ComparisonMsg::First(index) => self.first = index,
+            ComparisonMsg::Second(index) => self.second = index,
+        }
+        return true;
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        let count = ctx.props().results.len();
+        if self.first >= count || self.second >= count {
+            self.first = count.saturating_sub(2);
+            self.second = count.saturating_sub(1);
+        }
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let results = &ctx.props().results;
+        let ((_, first, first_sources), (_, second, second_sources)) =
+            match (results.get(self.first), results.get(self.second)) {
+                (Some(first), Some(second)) => (first, second),
+                _ => return html! {},
+            };
+        let result_diff = diff(first, first_sources, second, second_sources);
+        let label = |id: &String| ctx.props().labels.get(id).unwrap_or(id).clone();
+        let join = |ids: &Vec<String>| ids.iter().map(label).collect::<Vec<String>>().join(", ");
+        return html! {
+            <div class="comparison flex-vertical">
+                <div class="flex-horizontal">
+                    { self.view_select(ctx, self.first, ComparisonMsg::First) }
+                    { "compared to" }
+                    { self.view_select(ctx, self.second, ComparisonMsg::Second) }
+                </div>
+                <div>{ format!("Score: {} -> {} ({:+})", first.score, second.score, result_diff.score_delta) }</div>
+                <h4>{ format!("Moved elements ({})", result_diff.moved.len()) }</h4>
+                <table>
+                    <thead>
+                        <th>{ "Element" }</th>
+                        <th>{ "Before with" }</th>
+                        <th>{ "After with" }</th>
+                    </thead>
+                    <tbody>
+                        { result_diff.moved.iter().map(|moved| html! {
+                            <tr>
+                                <td>{ label(&moved.element) }</td>
+                                <td>{ join(&moved.before) }</td>
+                                <td>{ join(&moved.after) }</td>
+                            </tr>
+                        }).collect::<Vec<Html>>() }
+                    </tbody>
+                </table>
+                <h4>{ "Wishes met per rule" }</h4>
+                <ul>
+                    { result_diff.totals.iter().filter(|(_, (before, after))| before != after).map(|(source, (before, after))| html! {
+                        <li>{ format!("{}: {} -> {}", source, before, after) }</li>
+                    }).collect::<Vec<Html>>() }
+                </ul>
+                { self.view_one_sided("Only in the first result", &result_diff.only_before) }
+                { self.view_one_sided("Only in the second result", &result_diff.only_after) }
+                <ul>
+                    { result_diff.wishes.iter().map(|change| html! {
+                        <li class={ if change.satisfied { "" } else { "invalid" } }>
+                            { format!(
+                                "{} {}: {}",
+                                label(&change.element),
+                                if change.satisfied { "now satisfies" } else { "now violates" },
+                                change.source
+                            ) }
+                        </li>
+                    }).collect::<Vec<Html>>() }
+                </ul>
+            </div>
+        };
+    }
+}
+
+impl ResultComparison {
+    /// Rules and pairs that only one of the results was computed with, so their wishes aren't compared.
+    fn view_one_sided(&self, header: &str, sources: &[String]) -> Html {
+        if sources.is_empty() {
+            return html! {};
+        }
+        return html! {
+            <>
+                <h4>{ format!("{} ({})", header, sources.len()) }</h4>
+                <ul>
+                    { sources.iter().map(|source| html! { <li>{ source }</li> }).collect::<Vec<Html>>() }
+                </ul>
+            </>
+        };
+    }
+
+    fn view_select<F>(&self, ctx: &Context<Self>, selected: usize, to_msg: F) -> Html
+    where
+        F: Fn(usize) -> ComparisonMsg + 'static,
+    {
+        return html! {
+            <select onchange={ctx.link().batch_callback(move |e: Event| e.target_unchecked_into::<HtmlSelectElement>().value().parse::<usize>().ok().map(&to_msg))}>
+                { ctx.props().results.iter().enumerate().map(|(index, (number, result, _))| html! {
+                    <option value={ index.to_string() } selected={ index == selected }>
+                        { format!("Result {} ({})", number, result.score) }
+                    </option>
+                }).collect::<Vec<Html>>() }
+            </select>
+        };
+    }
+}
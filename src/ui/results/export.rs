@@ -0,0 +1,280 @@
+use crate::io::csv::{write_result, ExportLayout, ExportOptions};
+use crate::io::graph::{write_graph, GraphFormat};
+use crate::io::pdf::{write_pdf, PdfLayout, PdfOptions};
+use crate::io::spreadsheet::{write_workbook, SpreadsheetFormat};
+use crate::matching::data::{MatchingData, MatchingResult};
+use crate::matching::preference_graph;
+use crate::ui::generic::download::download;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+pub(crate) struct ResultExport {
+    options: ExportOptions,
+    pdf_layout: PdfLayout,
+    graph_format: GraphFormat,
+    graph_clusters: bool, // cluster the nodes of the graph by the groups of the result
+    error: Option<String>, // of the last download
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ResultExportProps {
+    pub results: Vec<(usize, MatchingResult)>, // result number and result, CSV is only offered for a single one
+    pub matching_data: MatchingData,
+}
+
+pub enum ExportMsg {
+    Layout(ExportLayout),
+    GroupNumber(bool),
+    GroupScore(bool),
+    MemberIds(bool),
+    Field(String, bool),
+    Delimiter(u8),
+    PdfLayout(PdfLayout),
+    GraphFormat(GraphFormat),
+    GraphClusters(bool),
+    Download(ExportFile),
+}
+
+/// Files are only built when they are downloaded, building all of them on every render is too slow.
+#[derive(Copy, Clone, PartialEq)]
+pub enum ExportFile {
+    Csv,
+    Workbook(SpreadsheetFormat),
+    Pdf,
+    Graph,
+}
+
+impl Component for ResultExport {
+    type Message = ExportMsg;
+    type Properties = ResultExportProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            options: ExportOptions::default(),
+            pdf_layout: PdfLayout::GroupPages,
+            graph_format: GraphFormat::Dot,
+            graph_clusters: true,
+            error: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ExportMsg::Layout(layout) => self.options.layout = layout,
+            ExportMsg::GroupNumber(checked) => self.options.group_number = checked,
+            ExportMsg::GroupScore(checked) => self.options.group_score = checked,
+            ExportMsg::MemberIds(checked) => self.options.member_ids = checked,
+            ExportMsg::Field(field, checked) => {
+                self.options.fields.retain(|other| *other != field);
+                if checked {
+                    self.options.fields.push(field);
+                    let fields = &ctx.props().matching_data.fields;
+                    self.options
+                        .fields
+                        .sort_by_key(|field| fields.get_index_of(field));
+                }
+            }
+            ExportMsg::Delimiter(delimiter) => self.options.delimiter = delimiter,
+            ExportMsg::PdfLayout(layout) => self.pdf_layout = layout,
+            ExportMsg::GraphFormat(format) => self.graph_format = format,
+            ExportMsg::GraphClusters(checked) => self.graph_clusters = checked,
+            ExportMsg::Download(file) => self.error = self.download(ctx, file).err(),
+        }
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let fields: Vec<(&String, &String)> = props.matching_data.fields.iter().collect();
+        let single = props.results.len() == 1; // CSV and PDF only hold one result
+        let button = |file: ExportFile, label: &str| {
+            html! {
+                <button onclick={ctx.link().callback(move |_| ExportMsg::Download(file))}>{ label }</button>
+            }
+        };
+        return html! {
+            <div class="export flex-horizontal">
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+                    ExportMsg::Layout(match &*value {
+                        "Long" => ExportLayout::Long,
+                        _ => ExportLayout::Wide,
+                    })
+                })}>
+                    { ExportLayout::values().iter().map(|layout| html! {
+                        <option value={ layout.to_string() } selected={ *layout == self.options.layout }>
+                            { match layout {
+                                ExportLayout::Wide => "Row per group",
+                                ExportLayout::Long => "Row per member",
+                            } }
+                        </option>
+                    }).collect::<Vec<Html>>() }
+                </select>
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+                    ExportMsg::Delimiter(value.parse::<u8>().unwrap_or(b','))
+                })}>
+                    { [(b',', ","), (b';', ";"), (b'\t', "Tab")].iter().map(|(value, name)| html! {
+                        <option value={ value.to_string() } selected={ self.options.delimiter == *value }>{ name }</option>
+                    }).collect::<Vec<Html>>() }
+                </select>
+                { self.view_checkbox(ctx, "Group", self.options.group_number, ExportMsg::GroupNumber) }
+                { self.view_checkbox(ctx, "Score", self.options.group_score, ExportMsg::GroupScore) }
+                { self.view_checkbox(ctx, "Id", self.options.member_ids, ExportMsg::MemberIds) }
+                { fields.into_iter().map(|(id, name)| {
+                    let id = id.clone();
+                    self.view_checkbox(ctx, name, self.options.fields.contains(&id), move |checked| {
+                        ExportMsg::Field(id.clone(), checked)
+                    })
+                }).collect::<Vec<Html>>() }
+                { if single { button(ExportFile::Csv, "Download CSV") } else { html! {} } }
+                { SpreadsheetFormat::values().into_iter().map(|format| button(ExportFile::Workbook(format), match format {
+                    SpreadsheetFormat::Xlsx => "Download Excel",
+                    SpreadsheetFormat::Ods => "Download OpenDocument",
+                })).collect::<Vec<Html>>() }
+                { if single { self.view_pdf(ctx) } else { html! {} } }
+                { self.view_graph(ctx) }
+                {
+                    match &self.error {
+                        Some(error) => html! { <span class="invalid">{ error }</span> },
+                        None => html! {},
+                    }
+                }
+            </div>
+        };
+    }
+}
+
+impl ResultExport {
+    /// Builds the file and hands it to the browser.
+    fn download(&self, ctx: &Context<Self>, file: ExportFile) -> Result<(), String> {
+        let props = ctx.props();
+        let data = &props.matching_data;
+        let single = match &props.results[..] {
+            [(number, result)] => Some((*number, result)),
+            _ => None,
+        };
+        let name = match single {
+            Some((number, _)) => format!("result-{}", number),
+            None => "results".to_string(),
+        };
+        match (file, single) {
+            (ExportFile::Csv, Some((_, result))) => {
+                let csv = write_result(result, data, &self.options)?;
+                download(
+                    &format!("{}.csv", name),
+                    "text/csv;charset=utf-8",
+                    csv.as_bytes(),
+                );
+            }
+            (ExportFile::Workbook(format), _) => {
+                let bytes = write_workbook(&props.results, data, &self.options, format)?;
+                download(
+                    &format!("{}.{}", name, format.extension()),
+                    format.mime(),
+                    &bytes,
+                );
+            }
+            (ExportFile::Pdf, Some((_, result))) => {
+                let options = PdfOptions {
+                    layout: self.pdf_layout,
+                    title: "".to_string(),
+                    fields: self.options.fields.clone(),
+                };
+                let bytes = write_pdf(result, data, &options);
+                download(&format!("{}.pdf", name), "application/pdf", &bytes);
+            }
+            (ExportFile::Graph, _) => {
+                let graph = preference_graph(data).map_err(|error| error.to_string())?;
+                let clusters = single
+                    .filter(|_| self.graph_clusters)
+                    .map(|(_, result)| result);
+                let format = self.graph_format;
+                let text = write_graph(&graph, data, clusters, format);
+                download(
+                    &format!("{}-graph.{}", name, format.extension()),
+                    format.mime(),
+                    text.as_bytes(),
+                );
+            }
+            _ => {} // CSV and PDF are not offered for several results
+        }
+        return Ok(());
+    }
+
+    /// Printable sheets of a single result, the checked fields are printed below the names.
+    fn view_pdf(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <>
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+                    ExportMsg::PdfLayout(match &*value {
+                        "NameTents" => PdfLayout::NameTents,
+                        "Overview" => PdfLayout::Overview,
+                        _ => PdfLayout::GroupPages,
+                    })
+                })}>
+                    { PdfLayout::values().iter().map(|layout| html! {
+                        <option value={ layout.to_string() } selected={ *layout == self.pdf_layout }>
+                            { match layout {
+                                PdfLayout::GroupPages => "Page per group",
+                                PdfLayout::NameTents => "Name tents",
+                                PdfLayout::Overview => "Overview list",
+                            } }
+                        </option>
+                    }).collect::<Vec<Html>>() }
+                </select>
+                <button onclick={ctx.link().callback(|_| ExportMsg::Download(ExportFile::Pdf))}>{ "Download PDF" }</button>
+            </>
+        };
+    }
+
+    /// Preference graph of the data, optionally clustered by the groups of a single result.
+    fn view_graph(&self, ctx: &Context<Self>) -> Html {
+        let format = self.graph_format;
+        return html! {
+            <>
+                <select onchange={ctx.link().callback(|e: Event| {
+                    let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+                    ExportMsg::GraphFormat(match &*value {
+                        "GraphMl" => GraphFormat::GraphMl,
+                        _ => GraphFormat::Dot,
+                    })
+                })}>
+                    { GraphFormat::values().iter().map(|value| html! {
+                        <option value={ value.to_string() } selected={ *value == format }>
+                            { match value {
+                                GraphFormat::Dot => "Graphviz",
+                                GraphFormat::GraphMl => "GraphML",
+                            } }
+                        </option>
+                    }).collect::<Vec<Html>>() }
+                </select>
+                {
+                    if ctx.props().results.len() == 1 {
+                        self.view_checkbox(ctx, "Cluster by group", self.graph_clusters, ExportMsg::GraphClusters)
+                    } else {
+                        html! {}
+                    }
+                }
+                <button onclick={ctx.link().callback(|_| ExportMsg::Download(ExportFile::Graph))}>{ "Download graph" }</button>
+            </>
+        };
+    }
+
+    fn view_checkbox<F>(&self, ctx: &Context<Self>, label: &str, checked: bool, to_msg: F) -> Html
+    where
+        F: Fn(bool) -> ExportMsg + 'static,
+    {
+        return html! {
+            <label>
+                <input
+                    type="checkbox"
+                    checked={ checked }
+                    onchange={ctx.link().callback(move |e: Event| to_msg(e.target_unchecked_into::<HtmlInputElement>().checked()))}
+                />
+                { label }
+            </label>
+        };
+    }
+}
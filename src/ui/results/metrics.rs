@@ -0,0 +1,151 @@
+use crate::matching::metrics::ElementMetrics;
+use crate::matching::score::ScoreSource;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use yew::prelude::*;
+
+pub(crate) struct MetricsTable {
+    sort: MetricsColumn,
+    descending: bool,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum MetricsColumn {
+    Element,
+    Group,
+    Received,
+    Satisfaction,
+    Rank,
+}
+
+impl MetricsColumn {
+    pub fn values() -> Vec<Self> {
+        return vec![
+            MetricsColumn::Element,
+            MetricsColumn::Group,
+            MetricsColumn::Received,
+            MetricsColumn::Satisfaction,
+            MetricsColumn::Rank,
+        ];
+    }
+
+    fn header(&self) -> &'static str {
+        return match self {
+            MetricsColumn::Element => "Element",
+            MetricsColumn::Group => "Group",
+            MetricsColumn::Received => "Points",
+            MetricsColumn::Satisfaction => "Wishes met",
+            MetricsColumn::Rank => "Group rank",
+        };
+    }
+
+    fn compare(&self, a: &ElementMetrics, b: &ElementMetrics) -> Ordering {
+        return match self {
+            MetricsColumn::Element => a.element.cmp(&b.element),
+            MetricsColumn::Group => a.group.cmp(&b.group),
+            MetricsColumn::Received => a.received.cmp(&b.received),
+            MetricsColumn::Satisfaction => a
+                .satisfaction()
+                .partial_cmp(&b.satisfaction())
+                .unwrap_or(Ordering::Equal),
+            MetricsColumn::Rank => a.rank.cmp(&b.rank),
+        };
+    }
+}
+
+pub enum MetricsMsg {
+    Sort(MetricsColumn),
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct MetricsTableProps {
+    pub metrics: Vec<ElementMetrics>,
+    pub labels: HashMap<String, String>, // element id -> label
+    pub sources: HashMap<ScoreSource, String>, // rule or pair -> description
+}
+
+impl Component for MetricsTable {
+    type Message = MetricsMsg;
+    type Properties = MetricsTableProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            sort: MetricsColumn::Satisfaction,
+            descending: false,
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            MetricsMsg::Sort(column) => {
+                if self.sort == column {
+                    self.descending = !self.descending;
+                } else {
+                    self.sort = column;
+                    self.descending = false;
+                }
+            }
+        }
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let mut metrics: Vec<&ElementMetrics> = ctx.props().metrics.iter().collect();
+        metrics.sort_by(|a, b| {
+            let ordering = self.sort.compare(a, b).then(a.element.cmp(&b.element));
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+        let least_satisfied = least_satisfied(&ctx.props().metrics);
+        return html! {
+            <table class="metrics">
+                <thead>
+                    { MetricsColumn::values().into_iter().map(|column| html! {
+                        <th onclick={ctx.link().callback(move |_| MetricsMsg::Sort(column))}>
+                            { column.header() }
+                            { if self.sort == column { if self.descending { " \u{25bc}" } else { " \u{25b2}" } } else { "" } }
+                        </th>
+                    }).collect::<Vec<Html>>() }
+                    <th>{ "Unmet wishes" }</th>
+                </thead>
+                <tbody>
+                    { metrics.iter().map(|metric| html! {
+                        <tr class={ if least_satisfied.contains(&metric.element) { "least-satisfied" } else { "" } }>
+                            <td>{ ctx.props().labels.get(&metric.element).unwrap_or(&metric.element) }</td>
+                            <td>{ metric.group + 1 }</td>
+                            <td>{ metric.received }</td>
+                            <td>{ format!("{} / {}", metric.satisfied(), metric.wishes.len()) }</td>
+                            <td>{ metric.rank }</td>
+                            <td>
+                                { metric.wishes.iter().filter(|wish| !wish.satisfied).map(|wish| html! {
+                                    <div>{ ctx.props().sources.get(&wish.source).cloned().unwrap_or_default() }</div>
+                                }).collect::<Vec<Html>>() }
+                            </td>
+                        </tr>
+                    }).collect::<Vec<Html>>() }
+                </tbody>
+            </table>
+        };
+    }
+}
+
+/// The tenth of the elements with the fewest met wishes and points, ignoring fully satisfied ones.
+fn least_satisfied(metrics: &[ElementMetrics]) -> HashSet<String> {
+    let mut sorted: Vec<&ElementMetrics> = metrics
+        .iter()
+        .filter(|metric| metric.satisfaction() < 1.0)
+        .collect();
+    sorted.sort_by(|a, b| {
+        MetricsColumn::Satisfaction
+            .compare(a, b)
+            .then(a.received.cmp(&b.received))
+    });
+    return sorted
+        .iter()
+        .take((metrics.len() / 10).max(1))
+        .map(|metric| metric.element.clone())
+        .collect();
+}
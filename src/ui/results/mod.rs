@@ -0,0 +1,5 @@
+pub mod diff;
+pub mod export;
+pub mod metrics;
+pub mod report;
+pub mod sensitivity;
@@ -0,0 +1,121 @@
+use crate::io::data_url;
+use crate::io::report::{render_report, BuiltinTemplate, ReportFormat};
+use crate::matching::data::{MatchingData, MatchingResult};
+use web_sys::{HtmlSelectElement, HtmlTextAreaElement};
+use yew::prelude::*;
+
+/// Renders a result with a template, starting from one of the built-in templates.
+pub(crate) struct ReportExport {
+    format: ReportFormat,
+    template: String,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct ReportExportProps {
+    pub number: usize,
+    pub result: MatchingResult,
+    pub matching_data: MatchingData,
+}
+
+pub enum ReportMsg {
+    Format(ReportFormat),
+    Builtin(BuiltinTemplate),
+    UpdateTemplate(String),
+}
+
+impl Component for ReportExport {
+    type Message = ReportMsg;
+    type Properties = ReportExportProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            format: ReportFormat::Markdown,
+            template: BuiltinTemplate::Announcement
+                .source(ReportFormat::Markdown)
+                .to_string(),
+        }
+    }
+
+    fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            ReportMsg::Format(format) => {
+                // built-in templates are switched along, edited ones are kept
+                if let Some(builtin) = BuiltinTemplate::values()
+                    .into_iter()
+                    .find(|builtin| builtin.source(self.format) == self.template)
+                {
+                    self.template = builtin.source(format).to_string();
+                }
+                self.format = format;
+            }
+            ReportMsg::Builtin(builtin) => self.template = builtin.source(self.format).to_string(),
+            ReportMsg::UpdateTemplate(template) => self.template = template,
+        }
+        return true;
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        let props = ctx.props();
+        let rendered = render_report(
+            &self.template,
+            self.format,
+            props.number,
+            &props.result,
+            &props.matching_data,
+        );
+        return html! {
+            <div class="report flex-vertical">
+                <div class="flex-horizontal">
+                    <select onchange={ctx.link().callback(|e: Event| {
+                        let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+                        ReportMsg::Format(ReportFormat::values().into_iter().find(|format| format.to_string() == value).unwrap_or(ReportFormat::Markdown))
+                    })}>
+                        { ReportFormat::values().into_iter().map(|format| html! {
+                            <option value={ format.to_string() } selected={ format == self.format }>{ format.to_string() }</option>
+                        }).collect::<Vec<Html>>() }
+                    </select>
+                    <select onchange={ctx.link().batch_callback(|e: Event| {
+                        let value = e.target_unchecked_into::<HtmlSelectElement>().value();
+                        BuiltinTemplate::values().into_iter().find(|builtin| builtin.to_string() == value).map(ReportMsg::Builtin)
+                    })}>
+                        <option value="" selected=true disabled=true>{ "Load built-in template" }</option>
+                        { BuiltinTemplate::values().into_iter().map(|builtin| html! {
+                            <option value={ builtin.to_string() }>
+                                { match builtin {
+                                    BuiltinTemplate::Announcement => "Announcement per group",
+                                    BuiltinTemplate::Participants => "Message per participant",
+                                } }
+                            </option>
+                        }).collect::<Vec<Html>>() }
+                    </select>
+                    {
+                        match &rendered {
+                            Ok(text) => html! {
+                                <a
+                                    class="button"
+                                    download={ format!("result-{}.{}", props.number, self.format.extension()) }
+                                    href={ data_url(self.format.mime(), text.as_bytes()) }
+                                >
+                                    { "Download report" }
+                                </a>
+                            },
+                            Err(_) => html! {},
+                        }
+                    }
+                </div>
+                <textarea
+                    class="edit"
+                    rows="10"
+                    value={self.template.clone()}
+                    oninput={ctx.link().callback(|e: InputEvent| ReportMsg::UpdateTemplate(e.target_unchecked_into::<HtmlTextAreaElement>().value()))}
+                />
+                {
+                    match &rendered {
+                        Ok(text) => html! { <pre class="preview">{ text }</pre> },
+                        Err(error) => html! { <pre class="invalid">{ error }</pre> },
+                    }
+                }
+            </div>
+        };
+    }
+}
@@ -0,0 +1,81 @@
+use crate::matching::score::ScoreSource;
+use crate::matching::sensitivity::RuleImpact;
+use std::collections::HashMap;
+use yew::prelude::*;
+
+pub(crate) struct SensitivityTable {}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct SensitivityTableProps {
+    pub impacts: Vec<RuleImpact>,
+    pub sources: HashMap<ScoreSource, String>, // rule or pair -> description
+    pub elements: usize,                       // number of elements, to spot dominant rules
+}
+
+impl Component for SensitivityTable {
+    type Message = ();
+    type Properties = SensitivityTableProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {}
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <table class="sensitivity">
+                <thead>
+                    <th>{ "Rule" }</th>
+                    <th>{ "Change" }</th>
+                    <th>{ "Score" }</th>
+                    <th>{ "Original score" }</th>
+                    <th>{ "Delta" }</th>
+                    <th>{ "Moved" }</th>
+                    <th>{ "Note" }</th>
+                </thead>
+                <tbody>
+                    { ctx.props().impacts.iter().map(|impact| html! {
+                        <tr>
+                            <td>{ ctx.props().sources.get(&ScoreSource::Rule(impact.rule)).cloned().unwrap_or_default() }</td>
+                            <td>{ impact.perturbation.to_string() }</td>
+                            {
+                                match &impact.outcome {
+                                    Ok(outcome) => html! {
+                                        <>
+                                            <td>{ outcome.score }</td>
+                                            <td>{ outcome.original_score }</td>
+                                            <td>{ format!("{:+}", outcome.score_delta) }</td>
+                                            <td>{ outcome.moved }</td>
+                                            <td>{ self.note(ctx, impact) }</td>
+                                        </>
+                                    },
+                                    Err(error) => html! {
+                                        <td colspan="5" class="invalid">{ error.to_string() }</td>
+                                    },
+                                }
+                            }
+                        </tr>
+                    }).collect::<Vec<Html>>() }
+                </tbody>
+            </table>
+        };
+    }
+}
+
+impl SensitivityTable {
+    /// Flags rules that change nothing when disabled, that move more than half of the elements,
+    /// or whose change leads to a grouping the unchanged rules don't allow.
+    fn note(&self, ctx: &Context<Self>, impact: &RuleImpact) -> &'static str {
+        let unchanged = ctx
+            .props()
+            .impacts
+            .iter()
+            .filter(|other| other.rule == impact.rule)
+            .all(|other| matches!(&other.outcome, Ok(outcome) if outcome.moved == 0));
+        return match &impact.outcome {
+            Ok(_) if unchanged => "no effect",
+            Ok(outcome) if !outcome.feasible => "breaks forced rules",
+            Ok(outcome) if outcome.moved * 2 > ctx.props().elements => "dominant",
+            _ => "",
+        };
+    }
+}
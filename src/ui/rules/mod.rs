@@ -1,7 +1,13 @@
+pub mod pairs;
+pub mod text_editor;
+
+use crate::matching::parser::print_conditions;
 use crate::matching::rules::{Rule, RuleOperand, RuleSeverity};
+use crate::matching::schema::FieldType;
 use crate::ui::generic::table::TabledDisplay;
-use crate::ui::ui::BaseMsg;
-use std::collections::HashMap;
+use crate::ui::ui::{BaseMsg, MatchingDataUpdate};
+use indexmap::IndexMap;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
 use yew::prelude::*;
 
 pub(crate) struct RuleDisplay {
@@ -11,6 +17,16 @@ pub(crate) struct RuleDisplay {
 pub enum RuleMsg {
     NewRule,
     Delete(usize),
+    Change(usize, RuleChange),
+}
+
+pub enum RuleChange {
+    Severity(String),
+    Field(String),
+    Value(Option<String>),
+    Operand(String),
+    TargetField(String),
+    TargetValue(Option<String>),
 }
 
 #[derive(Properties, Clone, PartialEq)]
@@ -18,7 +34,9 @@ pub struct RuleDisplayProps {
     #[prop_or_default]
     pub change_callback: Option<Callback<BaseMsg>>,
     pub rules: Vec<Rule>,
-    pub fields: HashMap<String, String>,
+    pub fields: IndexMap<String, String>,
+    #[prop_or_default]
+    pub schema: IndexMap<String, FieldType>,
 }
 
 impl Component for RuleDisplay {
@@ -31,8 +49,11 @@ impl Component for RuleDisplay {
                 "Actions".to_string(),
                 "Severity".into(),
                 "Column".into(),
+                "Value".into(),
                 "Operand".into(),
                 "Target Column".into(),
+                "Target Value".into(),
+                "Conditions".into(),
             ]),
         }
     }
@@ -44,10 +65,37 @@ impl Component for RuleDisplay {
             RuleMsg::Delete(index) => {
                 rules.remove(index);
             }
-            _ => unimplemented!(),
+            RuleMsg::Change(index, change) => {
+                let rule = &mut rules[index];
+                match change {
+                    RuleChange::Severity(name) => {
+                        if let Some(severity) = RuleSeverity::values()
+                            .into_iter()
+                            .find(|severity| severity.to_string() == name)
+                        {
+                            rule.severity = severity;
+                        }
+                    }
+                    RuleChange::Field(field) => rule.field = field,
+                    RuleChange::Value(value) => rule.value = value,
+                    RuleChange::Operand(name) => {
+                        if let Some(operand) = RuleOperand::values()
+                            .into_iter()
+                            .find(|operand| operand.to_string() == name)
+                        {
+                            rule.operand = operand;
+                        }
+                    }
+                    RuleChange::TargetField(field) => rule.target_field = field,
+                    RuleChange::TargetValue(value) => rule.target_value = value,
+                }
+            }
         };
         if let Some(callback) = &ctx.props().change_callback {
-            callback.emit(BaseMsg::UpdateMatchingData(None, None, Some(rules), None));
+            callback.emit(BaseMsg::UpdateMatchingData(Box::new(MatchingDataUpdate {
+                rules: Some(rules),
+                ..MatchingDataUpdate::default()
+            })));
         }
         false // redraw triggered by parent
     }
@@ -64,72 +112,121 @@ impl Component for RuleDisplay {
                 data={Vec::new()}
             >
                 {
-                    ctx.props().rules.iter().enumerate().map(|(index, rule)| html! {
-                        <tr class={ if rule.is_valid() {""} else {"invalid"} }>
-                            <td>
-                                <button onclick={ctx.link().callback(move |_| RuleMsg::Delete(index))}>{ "-" }</button>
-                            </td>
-                            <td>
-                                <select>
-                                  { RuleSeverity::values().iter().map(|name| html! {
-                                    <option
-                                        value={ name.to_string() }
-                                        selected={ rule.severity == *name }
-                                    >
-                                        { name.to_string() }
-                                    </option>
-                                  }).collect::<Vec<Html>>() }
-                                </select>
-                            </td>
-                            <td>
-                                <select>
-                                  { ctx.props().fields.iter().map(|(id, name)| html! {
-                                    <option
-                                        value={ id.clone() }
-                                        selected={ rule.field == *id }
-                                    >
-                                        { name.clone() }
-                                    </option>
-                                  }).collect::<Vec<Html>>() }
-                                </select>
-                            </td>
-                            <td>
-                                <select>
-                                  { RuleOperand::values().iter().map(|name| html! {
-                                    <option
-                                        value={ name.to_string() }
-                                        selected={ rule.operand == *name }
-                                    >
-                                        { name.to_string() }
-                                    </option>
-                                  }).collect::<Vec<Html>>() }
-                                </select>
-                            </td>
-                            <td>
-                                <select>
-                                  { ctx.props().fields.iter().map(|(id, name)| html! {
-                                    <option
-                                        value={ id.clone() }
-                                        selected={ rule.target_field == *id }
-                                    >
-                                        { name.clone() }
-                                    </option>
-                                  }).collect::<Vec<Html>>() }
-                                </select>
-                            </td>
-                        </tr>
-                    }).collect::<Vec<Html>>()
+                    ctx.props().rules.iter().enumerate().map(|(index, rule)| self.view_rule(ctx, index, rule)).collect::<Vec<Html>>()
                 }
                 <tr>
                     <td style="text-align: right;">
                         <button onclick={ctx.link().callback(move |_| RuleMsg::NewRule)}>{ "+" }</button>
                     </td>
-                    <td/>
-                    <td/>
-                    <td/>
-                    <td/>
+                    { self.headers.iter().skip(1).map(|_| html! { <td/> }).collect::<Vec<Html>>() }
                 </tr>
             </TabledDisplay<std::vec::Vec<String>, std::vec::Vec<std::vec::Vec<String>>>>
         };
     }
 }
+
+impl RuleDisplay {
+    /// Operands offered for the type of the compared field, always including the current one.
+    fn operands(&self, ctx: &Context<Self>, rule: &Rule) -> Vec<RuleOperand> {
+        let field = if rule.field.is_empty() {
+            &rule.target_field
+        } else {
+            &rule.field
+        };
+        let mut operands = ctx
+            .props()
+            .schema
+            .get(field)
+            .unwrap_or(&FieldType::Text)
+            .operands();
+        if !operands.contains(&rule.operand) {
+            operands.push(rule.operand);
+        }
+        return operands;
+    }
+
+    fn view_rule(&self, ctx: &Context<Self>, index: usize, rule: &Rule) -> Html {
+        return html! {
+            <tr class={ if rule.is_valid() {""} else {"invalid"} }>
+                <td>
+                    <button onclick={ctx.link().callback(move |_| RuleMsg::Delete(index))}>{ "-" }</button>
+                </td>
+                <td>
+                    <select onchange={ctx.link().callback(move |e: Event| RuleMsg::Change(index, RuleChange::Severity(e.target_unchecked_into::<HtmlSelectElement>().value())))}>
+                      { RuleSeverity::values().iter().map(|name| html! {
+                        <option
+                            value={ name.to_string() }
+                            selected={ rule.severity == *name }
+                        >
+                            { name.to_string() }
+                        </option>
+                      }).collect::<Vec<Html>>() }
+                    </select>
+                </td>
+                <td>
+                    { self.view_field_select(ctx, &rule.field, move |field| RuleMsg::Change(index, RuleChange::Field(field))) }
+                </td>
+                <td>
+                    { self.view_value_input(ctx, &rule.value, move |value| RuleMsg::Change(index, RuleChange::Value(value))) }
+                </td>
+                <td>
+                    { if rule.negated { "not " } else { "" } }
+                    <select onchange={ctx.link().callback(move |e: Event| RuleMsg::Change(index, RuleChange::Operand(e.target_unchecked_into::<HtmlSelectElement>().value())))}>
+                      { self.operands(ctx, rule).iter().map(|name| html! {
+                        <option
+                            value={ name.to_string() }
+                            selected={ rule.operand == *name }
+                        >
+                            { name.to_string() }
+                        </option>
+                      }).collect::<Vec<Html>>() }
+                    </select>
+                </td>
+                <td>
+                    { self.view_field_select(ctx, &rule.target_field, move |field| RuleMsg::Change(index, RuleChange::TargetField(field))) }
+                </td>
+                <td>
+                    { self.view_value_input(ctx, &rule.target_value, move |value| RuleMsg::Change(index, RuleChange::TargetValue(value))) }
+                </td>
+                <td>{ print_conditions(rule, &ctx.props().fields) }</td>
+            </tr>
+        };
+    }
+
+    fn view_field_select<F>(&self, ctx: &Context<Self>, selected: &String, to_msg: F) -> Html
+    where
+        F: Fn(String) -> RuleMsg + 'static,
+    {
+        let fields: Vec<(&String, &String)> = ctx.props().fields.iter().collect();
+        return html! {
+            <select onchange={ctx.link().callback(move |e: Event| to_msg(e.target_unchecked_into::<HtmlSelectElement>().value()))}>
+                <option value="" selected={ selected.is_empty() }>{ "-" }</option>
+                { fields.iter().map(|(id, name)| html! {
+                    <option
+                        value={ (*id).clone() }
+                        selected={ selected == *id }
+                    >
+                        { (*name).clone() }
+                    </option>
+                }).collect::<Vec<Html>>() }
+            </select>
+        };
+    }
+
+    fn view_value_input<F>(&self, ctx: &Context<Self>, value: &Option<String>, to_msg: F) -> Html
+    where
+        F: Fn(Option<String>) -> RuleMsg + 'static,
+    {
+        return html! {
+            <input
+                type="text"
+                placeholder="any value"
+                value={ value.clone().unwrap_or_default() }
+                onchange={ctx.link().callback(move |e: Event| {
+                    let value = e.target_unchecked_into::<HtmlInputElement>().value();
+                    to_msg(if value.is_empty() { None } else { Some(value) })
+                })}
+            />
+        };
+    }
+}
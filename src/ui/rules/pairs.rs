@@ -0,0 +1,159 @@
+use crate::matching::pairs::PairRule;
+use crate::matching::rules::RuleSeverity;
+use crate::ui::generic::table::TabledDisplay;
+use crate::ui::ui::{BaseMsg, MatchingDataUpdate};
+use indexmap::IndexMap;
+use web_sys::{HtmlInputElement, HtmlSelectElement};
+use yew::prelude::*;
+
+pub(crate) struct PairDisplay {
+    headers: Vec<String>,
+}
+
+pub enum PairMsg {
+    NewPair,
+    Delete(usize),
+    Change(usize, PairChange),
+}
+
+pub enum PairChange {
+    First(String),
+    Second(String),
+    Severity(String),
+    Weight(Option<i16>),
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct PairDisplayProps {
+    #[prop_or_default]
+    pub change_callback: Option<Callback<BaseMsg>>,
+    pub pairs: Vec<PairRule>,
+    pub labels: IndexMap<String, String>, // element id -> label, in row order
+}
+
+impl Component for PairDisplay {
+    type Message = PairMsg;
+    type Properties = PairDisplayProps;
+
+    fn create(_ctx: &Context<Self>) -> Self {
+        Self {
+            headers: Vec::from([
+                "Actions".to_string(),
+                "Element".into(),
+                "Other Element".into(),
+                "Severity".into(),
+                "Weight".into(),
+            ]),
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let mut pairs = ctx.props().pairs.clone();
+        match msg {
+            PairMsg::NewPair => pairs.push(PairRule::new()),
+            PairMsg::Delete(index) => {
+                pairs.remove(index);
+            }
+            PairMsg::Change(index, change) => {
+                let pair = &mut pairs[index];
+                match change {
+                    PairChange::First(id) => pair.first = id,
+                    PairChange::Second(id) => pair.second = id,
+                    PairChange::Severity(name) => {
+                        if let Some(severity) = RuleSeverity::values()
+                            .into_iter()
+                            .find(|severity| severity.to_string() == name)
+                        {
+                            pair.severity = severity;
+                        }
+                    }
+                    PairChange::Weight(weight) => pair.weight = weight,
+                }
+            }
+        };
+        if let Some(callback) = &ctx.props().change_callback {
+            callback.emit(BaseMsg::UpdateMatchingData(Box::new(MatchingDataUpdate {
+                pairs: Some(pairs),
+                ..MatchingDataUpdate::default()
+            })));
+        }
+        false // redraw triggered by parent
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <TabledDisplay<std::vec::Vec<String>, std::vec::Vec<std::vec::Vec<String>>>
+                headers={self.headers.clone()}
+                data={Vec::new()}
+            >
+                {
+                    ctx.props().pairs.iter().enumerate().map(|(index, pair)| html! {
+                        <tr class={ if pair.is_valid() {""} else {"invalid"} }>
+                            <td>
+                                <button onclick={ctx.link().callback(move |_| PairMsg::Delete(index))}>{ "-" }</button>
+                            </td>
+                            <td>
+                                { self.view_element_select(ctx, &pair.first, move |id| PairMsg::Change(index, PairChange::First(id))) }
+                            </td>
+                            <td>
+                                { self.view_element_select(ctx, &pair.second, move |id| PairMsg::Change(index, PairChange::Second(id))) }
+                            </td>
+                            <td>
+                                <select onchange={ctx.link().callback(move |e: Event| PairMsg::Change(index, PairChange::Severity(e.target_unchecked_into::<HtmlSelectElement>().value())))}>
+                                  { RuleSeverity::values().iter().map(|name| html! {
+                                    <option
+                                        value={ name.to_string() }
+                                        selected={ pair.severity == *name }
+                                    >
+                                        { name.to_string() }
+                                    </option>
+                                  }).collect::<Vec<Html>>() }
+                                </select>
+                            </td>
+                            <td>
+                                <input
+                                    type="number"
+                                    min="0"
+                                    placeholder="default"
+                                    value={ pair.weight.map(|weight| weight.to_string()).unwrap_or_default() }
+                                    onchange={ctx.link().callback(move |e: Event| PairMsg::Change(index, PairChange::Weight(e.target_unchecked_into::<HtmlInputElement>().value().parse::<i16>().ok())))}
+                                />
+                            </td>
+                        </tr>
+                    }).collect::<Vec<Html>>()
+                }
+                <tr>
+                    <td style="text-align: right;">
+                        <button onclick={ctx.link().callback(move |_| PairMsg::NewPair)}>{ "+" }</button>
+                    </td>
+                    <td/>
+                    <td/>
+                    <td/>
+                    <td/>
+                </tr>
+            </TabledDisplay<std::vec::Vec<String>, std::vec::Vec<std::vec::Vec<String>>>>
+        };
+    }
+}
+
+impl PairDisplay {
+    fn view_element_select<F>(&self, ctx: &Context<Self>, selected: &String, to_msg: F) -> Html
+    where
+        F: Fn(String) -> PairMsg + 'static,
+    {
+        let labels: Vec<(&String, &String)> = ctx.props().labels.iter().collect();
+        return html! {
+            <select onchange={ctx.link().callback(move |e: Event| to_msg(e.target_unchecked_into::<HtmlSelectElement>().value()))}>
+                <option value="" selected={ selected.is_empty() }>{ "-" }</option>
+                { labels.iter().map(|(id, label)| html! {
+                    <option
+                        value={ (*id).clone() }
+                        selected={ selected == *id }
+                    >
+                        { (*label).clone() }
+                    </option>
+                }).collect::<Vec<Html>>() }
+            </select>
+        };
+    }
+}
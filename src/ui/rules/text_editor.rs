@@ -0,0 +1,94 @@
+use crate::matching::parser::{parse_rules, print_rules};
+use crate::matching::rules::Rule;
+use crate::ui::ui::{BaseMsg, MatchingDataUpdate};
+use indexmap::IndexMap;
+use web_sys::HtmlTextAreaElement;
+use yew::prelude::*;
+
+pub(crate) struct RuleTextEditor {
+    text: String,
+    error: Option<String>,
+}
+
+pub enum RuleTextMsg {
+    UpdateText(String),
+    Apply,
+    Reset,
+}
+
+#[derive(Properties, Clone, PartialEq)]
+pub struct RuleTextEditorProps {
+    #[prop_or_default]
+    pub change_callback: Option<Callback<BaseMsg>>,
+    pub rules: Vec<Rule>,
+    pub fields: IndexMap<String, String>,
+}
+
+impl Component for RuleTextEditor {
+    type Message = RuleTextMsg;
+    type Properties = RuleTextEditorProps;
+
+    fn create(ctx: &Context<Self>) -> Self {
+        Self {
+            text: print_rules(&ctx.props().rules, &ctx.props().fields),
+            error: None,
+        }
+    }
+
+    fn update(&mut self, ctx: &Context<Self>, msg: Self::Message) -> bool {
+        match msg {
+            RuleTextMsg::UpdateText(text) => {
+                self.text = text;
+                return false;
+            }
+            RuleTextMsg::Apply => match parse_rules(&self.text, &ctx.props().fields) {
+                Ok(rules) => {
+                    self.error = None;
+                    if let Some(callback) = &ctx.props().change_callback {
+                        callback.emit(BaseMsg::UpdateMatchingData(Box::new(MatchingDataUpdate {
+                            rules: Some(rules),
+                            ..MatchingDataUpdate::default()
+                        })));
+                    }
+                }
+                Err(error) => self.error = Some(error.render(&self.text)),
+            },
+            RuleTextMsg::Reset => {
+                self.text = print_rules(&ctx.props().rules, &ctx.props().fields);
+                self.error = None;
+            }
+        }
+        return true;
+    }
+
+    fn changed(&mut self, ctx: &Context<Self>) -> bool {
+        // rules changed elsewhere (or were applied from here), show their normalized text
+        self.text = print_rules(&ctx.props().rules, &ctx.props().fields);
+        self.error = None;
+        true
+    }
+
+    fn view(&self, ctx: &Context<Self>) -> Html {
+        return html! {
+            <div class="flex-vertical">
+                <textarea
+                    class="edit rule-text"
+                    rows="10"
+                    value={self.text.clone()}
+                    oninput={ctx.link().callback(|e: InputEvent| RuleTextMsg::UpdateText(e.target_unchecked_into::<HtmlTextAreaElement>().value()))}
+                />
+                {
+                    if let Some(error) = &self.error {
+                        html! { <pre class="invalid">{ error }</pre> }
+                    } else {
+                        html! {}
+                    }
+                }
+                <div class="flex-horizontal">
+                    <button onclick={ctx.link().callback(|_| RuleTextMsg::Apply)}>{ "Apply rules" }</button>
+                    <button onclick={ctx.link().callback(|_| RuleTextMsg::Reset)}>{ "Reset" }</button>
+                </div>
+            </div>
+        };
+    }
+}
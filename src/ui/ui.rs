@@ -1,30 +1,69 @@
-use crate::matching::data::{MatchingData, MatchingResult};
-use crate::matching::process;
+use crate::matching::data::{MatchingData, MatchingResult, RawValue};
+use crate::matching::diff::Sources;
+use crate::matching::history::{input_hash, RunKind, RunRecord};
+use crate::matching::sensitivity::{analyze, RuleImpact};
+use crate::matching::{MatchingError, SolverSettings};
 use crate::ui::generic::collapsable::Collapsable;
 use crate::ui::generic::table::TabledDisplay;
-use crate::ui::input::json_loader::JsonLoader;
+use crate::ui::input::csv_loader::CsvLoader;
+use crate::ui::input::grouping_loader::GroupingLoader;
+use crate::ui::input::project_loader::ProjectLoader;
+use crate::ui::input::schema_editor::SchemaEditor;
+use crate::ui::input::score_loader::ScoreLoader;
+use crate::ui::results::diff::ResultComparison;
+use crate::ui::results::export::ResultExport;
+use crate::ui::results::metrics::MetricsTable;
+use crate::ui::results::report::ReportExport;
+use crate::ui::results::sensitivity::SensitivityTable;
+use crate::ui::rules::pairs::PairDisplay;
+use crate::ui::rules::text_editor::RuleTextEditor;
 use crate::ui::rules::RuleDisplay;
 
 use yew::{html, Component, Context, Html};
 
 use crate::matching::connections::Connections;
+use crate::matching::layers::ScoreLayer;
+use crate::matching::pairs::PairRule;
+use crate::matching::parser::print_rule;
 use crate::matching::rules::Rule;
-use std::collections::HashMap;
+use crate::matching::schema::FieldType;
+use crate::matching::score::{GroupScore, ScoreSource};
+use crate::matching::validation::ValidationReport;
+use indexmap::IndexMap;
+use std::collections::{BTreeMap, HashMap};
 
 pub(crate) struct BaseModel {
     matching_data: Option<MatchingData>,
     connections: Option<Connections>,
-    results: Option<Vec<MatchingResult>>,
+    results: Option<Vec<RunRecord>>, // every result with how it was computed
+    sensitivity: Option<Result<Vec<RuleImpact>, MatchingError>>,
+    input_hash: Option<String>, // of the current data, to tell which results can be reproduced
+    validation: ValidationReport, // of the current data
+}
+
+/// Parts of the matching data to replace, the others are kept.
+#[derive(Default)]
+pub struct MatchingDataUpdate {
+    pub fields: Option<IndexMap<String, String>>,
+    pub elements: Option<IndexMap<String, IndexMap<String, RawValue>>>,
+    pub rules: Option<Vec<Rule>>,
+    pub outputs: Option<BTreeMap<usize, i16>>,
+    pub pairs: Option<Vec<PairRule>>,
+    pub schema: Option<IndexMap<String, FieldType>>,
+    pub layers: Option<Vec<ScoreLayer>>,
+    pub name_field: Option<Option<String>>, // Some(None) goes back to the first field
 }
 
 pub enum BaseMsg {
-    UpdateMatchingData(
-        /* fields: */ Option<HashMap<String, String>>,
-        /* elements: */ Option<HashMap<String, HashMap<String, String>>>,
-        /* rules: */ Option<Vec<Rule>>,
-        /* outputs: */ Option<HashMap<usize, i16>>,
-    ),
+    UpdateMatchingData(Box<MatchingDataUpdate>), // boxed, as it is much larger than the other messages
+    LoadHistory(Vec<RunRecord>), // replaces the results, e.g. with those of a loaded project
+    Reproduce(usize),            // index of the result to compute again
+    MoveField(String, isize),    // field id, offset in columns
+    MoveElement(String, isize),  // element id, offset in rows
+    SetNameField(String),        // field id holding the names of the elements
     Process,
+    VerifyGrouping(Vec<Vec<String>>), // element ids per group
+    AnalyzeRules,
 }
 impl Component for BaseModel {
     type Message = BaseMsg;
@@ -35,44 +74,113 @@ impl Component for BaseModel {
             matching_data: None,
             connections: None,
             results: None,
+            sensitivity: None,
+            input_hash: None,
+            validation: ValidationReport::default(),
         }
     }
 
     fn update(&mut self, _ctx: &Context<Self>, msg: Self::Message) -> bool {
+        let data_changed = matches!(
+            msg,
+            BaseMsg::UpdateMatchingData(..)
+                | BaseMsg::MoveField(..)
+                | BaseMsg::MoveElement(..)
+                | BaseMsg::SetNameField(_)
+        );
         match msg {
-            BaseMsg::UpdateMatchingData(fields_opt, elements_opt, rules_opt, outputs_opt) => {
+            BaseMsg::UpdateMatchingData(update) => {
                 if self.matching_data.is_none() {
                     self.matching_data = Some(MatchingData::new());
                 }
-                let mut matching_data = self.matching_data.as_mut().expect("Impossible to reach!");
-                if let Some(fields) = fields_opt {
+                let matching_data = self.matching_data.as_mut().expect("Impossible to reach!");
+                if let Some(fields) = update.fields {
                     matching_data.fields = fields;
                 }
-                if let Some(elements) = elements_opt {
+                if let Some(elements) = update.elements {
                     matching_data.elements = elements;
                 }
-                if let Some(rules) = rules_opt {
+                if let Some(rules) = update.rules {
                     matching_data.rules = rules;
                 }
-                if let Some(outputs) = outputs_opt {
+                if let Some(outputs) = update.outputs {
                     matching_data.outputs = outputs;
                 }
+                if let Some(pairs) = update.pairs {
+                    matching_data.pairs = pairs;
+                }
+                if let Some(schema) = update.schema {
+                    matching_data.schema = schema;
+                }
+                if let Some(layers) = update.layers {
+                    matching_data.layers = layers;
+                }
+                if let Some(name_field) = update.name_field {
+                    matching_data.name_field = name_field;
+                }
+                self.sensitivity = None; // computed for the previous rules
             }
-            BaseMsg::Process => {
-                if self.results.is_none() {
-                    self.results = Some(Vec::new());
+            BaseMsg::MoveField(id, offset) => match self.matching_data.as_mut() {
+                Some(matching_data) => matching_data.move_field(&id, offset),
+                None => return false,
+            },
+            BaseMsg::MoveElement(id, offset) => match self.matching_data.as_mut() {
+                Some(matching_data) => matching_data.move_element(&id, offset),
+                None => return false,
+            },
+            BaseMsg::SetNameField(field) => match self.matching_data.as_mut() {
+                Some(matching_data) => matching_data.name_field = Some(field),
+                None => return false,
+            },
+            BaseMsg::Process => self.add_result(RunRecord::run(
+                self.matching_data
+                    .as_ref()
+                    .expect("No matching data to process..."),
+                RunKind::Process,
+                &SolverSettings::default(),
+                now(),
+            )),
+            BaseMsg::LoadHistory(history) => {
+                self.results = if history.is_empty() {
+                    None
+                } else {
+                    Some(history)
+                };
+            }
+            BaseMsg::Reproduce(index) => {
+                let record = match self.results.as_ref().and_then(|results| results.get(index)) {
+                    Some(record) => record,
+                    None => return false,
+                };
+                let matching_data = self
+                    .matching_data
+                    .as_ref()
+                    .expect("No matching data to reproduce with...");
+                match record.reproduce(matching_data, now()) {
+                    Ok(record) => self.add_result(record),
+                    Err(_) => return false, // only offered while the data is unchanged
                 }
-                self.results
-                    .as_mut()
-                    .expect("Impossible state...")
-                    .push(process(
-                        self.matching_data
-                            .as_ref()
-                            .expect("No matching data to process..."),
-                    ))
             }
-            _ => return false,
+            BaseMsg::AnalyzeRules => {
+                self.sensitivity = Some(analyze(
+                    self.matching_data
+                        .as_ref()
+                        .expect("No matching data to analyze..."),
+                    &SolverSettings::default(),
+                ))
+            }
+            BaseMsg::VerifyGrouping(groups) => self.add_result(RunRecord::run(
+                self.matching_data
+                    .as_ref()
+                    .expect("No matching data to verify against..."),
+                RunKind::Verify(groups),
+                &SolverSettings::default(),
+                now(),
+            )),
         };
+        if data_changed {
+            self.data_changed();
+        }
         return true;
     }
 
@@ -83,14 +191,37 @@ impl Component for BaseModel {
                     <Collapsable header="Info">
                         { "Some info ..." }
                     </Collapsable>
-                    <JsonLoader change_callback={Some(ctx.link().callback(move |msg| msg))}/>
+                    <ProjectLoader
+                        change_callback={Some(ctx.link().callback(move |msg| msg))}
+                        matching_data={self.matching_data.clone()}
+                        history={self.results.clone().unwrap_or_default()}
+                    />
+                    <Collapsable header="Import CSV or spreadsheet">
+                        <CsvLoader
+                            fields={self.matching_data.as_ref().map(|matching_data| matching_data.fields.clone()).unwrap_or_default()}
+                            change_callback={Some(ctx.link().callback(move |msg| msg))}
+                        />
+                    </Collapsable>
                     {
                         if_exists(&self.matching_data, |matching_data| {
                             html! {
-                                <TabledDisplay<std::collections::HashMap<String, String>, std::collections::HashMap<String, std::collections::HashMap<String, String>>>
-                                    headers={matching_data.fields.clone()}
-                                    data={matching_data.elements.clone()}
-                                />
+                                <>
+                                    <TabledDisplay<IndexMap<String, String>, IndexMap<String, IndexMap<String, RawValue>>>
+                                        headers={matching_data.fields.clone()}
+                                        data={matching_data.elements.clone()}
+                                        move_header={Some(ctx.link().callback(|(id, offset)| BaseMsg::MoveField(id, offset)))}
+                                        move_row={Some(ctx.link().callback(|(id, offset)| BaseMsg::MoveElement(id, offset)))}
+                                    />
+                                    <Collapsable header="Field types">
+                                        <SchemaEditor
+                                            fields={matching_data.fields.clone()}
+                                            schema={matching_data.schema.clone()}
+                                            name_field={matching_data.name_field().cloned()}
+                                            change_callback={Some(ctx.link().callback(move |msg| msg))}
+                                        />
+                                    </Collapsable>
+                                    { self.view_validation() }
+                                </>
                             }
                         })
                     }
@@ -103,9 +234,48 @@ impl Component for BaseModel {
                                     <RuleDisplay
                                         rules={matching_data.rules.clone()}
                                         fields={matching_data.fields.clone()}
+                                        schema={matching_data.schema.clone()}
                                         change_callback={Some(ctx.link().callback(move |msg| msg))}
                                     />
-                                    <button onclick={ctx.link().callback(|_| BaseMsg::Process)}>{ "Process" }</button>
+                                    <Collapsable header="Pairs">
+                                        <PairDisplay
+                                            pairs={matching_data.pairs.clone()}
+                                            labels={matching_data.elements.keys().map(|id| (id.clone(), matching_data.element_label(id))).collect::<IndexMap<String, String>>()}
+                                            change_callback={Some(ctx.link().callback(move |msg| msg))}
+                                        />
+                                    </Collapsable>
+                                    <Collapsable header="Score layers">
+                                        <ScoreLoader
+                                            layers={matching_data.layers.clone()}
+                                            labels={matching_data.elements.keys().map(|id| (id.clone(), matching_data.element_label(id))).collect::<IndexMap<String, String>>()}
+                                            change_callback={Some(ctx.link().callback(move |msg| msg))}
+                                        />
+                                    </Collapsable>
+                                    <Collapsable header="Edit as text">
+                                        <RuleTextEditor
+                                            rules={matching_data.rules.clone()}
+                                            fields={matching_data.fields.clone()}
+                                            change_callback={Some(ctx.link().callback(move |msg| msg))}
+                                        />
+                                    </Collapsable>
+                                    <button
+                                        onclick={ctx.link().callback(|_| BaseMsg::Process)}
+                                        disabled={self.validation.has_errors()}
+                                    >
+                                        { "Process" }
+                                    </button>
+                                    <Collapsable header="Rule sensitivity">
+                                        <button
+                                            onclick={ctx.link().callback(|_| BaseMsg::AnalyzeRules)}
+                                            disabled={self.validation.has_errors()}
+                                        >
+                                            { "Analyze rules" }
+                                        </button>
+                                        { self.view_sensitivity(matching_data) }
+                                    </Collapsable>
+                                    <Collapsable header="Verify a grouping">
+                                        <GroupingLoader change_callback={Some(ctx.link().callback(move |msg| msg))}/>
+                                    </Collapsable>
                                 </>
                             }
                         })
@@ -113,10 +283,15 @@ impl Component for BaseModel {
                 </Collapsable>
                 <Collapsable header="Result" enabled={self.results.is_some()}>
                     {
-                        if_exists(&self.results, |matching_data| {
+                        if_exists(&self.results, |results| {
                             html! {
                                 <div class="result-list">
-                                    { if let Some(results) = &self.results { results.iter().map(|res| self.view_result(res)).collect::<Vec<Html>>() } else {vec![html!{}]}}
+                                    { self.view_export(results) }
+                                    { self.view_comparison(results) }
+                                    { results.iter().enumerate().map(|(index, record)| match &record.outcome {
+                                        Ok(result) => self.view_result(ctx, index + 1, record, result),
+                                        Err(error) => self.view_error(ctx, index + 1, record, error),
+                                    }).collect::<Vec<Html>>() }
                                 </div>
                             }
                         })
@@ -128,61 +303,307 @@ impl Component for BaseModel {
 }
 
 impl BaseModel {
+    /// Updates what is derived from the data, so it isn't computed again on every render.
+    fn data_changed(&mut self) {
+        self.input_hash = self.matching_data.as_ref().map(input_hash);
+        self.validation = self
+            .matching_data
+            .as_ref()
+            .map(MatchingData::validate)
+            .unwrap_or_default();
+    }
+
+    fn add_result(&mut self, record: RunRecord) {
+        self.results.get_or_insert_with(Vec::new).push(record);
+    }
+
+    /// Problems of the data, processing is only possible once there are no errors left.
+    fn view_validation(&self) -> Html {
+        let report = &self.validation;
+        if report.issues.is_empty() {
+            return html! {};
+        }
+        return html! {
+            <ul class="validation">
+                { report.issues.iter().map(|issue| html! {
+                    <li class={ issue.severity.to_string().to_lowercase() }>{ issue.to_string() }</li>
+                }).collect::<Vec<Html>>() }
+            </ul>
+        };
+    }
+
     fn matching_data(&self) -> &MatchingData {
         self.matching_data
             .as_ref()
             .expect("Matching data referenced but not set yet!")
     }
 
-    fn view_result(&self, result: &MatchingResult) -> Html {
+    fn view_result(
+        &self,
+        ctx: &Context<Self>,
+        number: usize,
+        record: &RunRecord,
+        result: &MatchingResult,
+    ) -> Html {
         let max_size = result
             .connections
             .iter()
             .map(|row| row.len())
             .max()
-            .expect("Could not get max with for result table!");
+            .unwrap_or(0);
         let mut headers: Vec<String> = Vec::new();
         headers.push("Group".parse().unwrap());
         for counter in 1..(max_size + 1) {
             headers.push(format!("Member {}", counter));
         }
-        let mut counter = 1;
-        let display_value_key = "group1";
-        let elements = &self
+        let matching_data = self
             .matching_data
             .as_ref()
-            .expect("Result to display but no matching data available...")
-            .elements;
-        let data: Vec<Vec<String>> = result
-            .connections
+            .expect("Result to display but no matching data available...");
+        let columns = headers.len();
+        return html! {
+        <div class="result">
+            <div class="result-header">
+                <div> { format!("Result {}: {}", number, result.score) } </div>
+                { self.view_run_info(ctx, number, record) }
+                <ResultExport
+                    results={vec![(number, result.clone())]}
+                    matching_data={matching_data.clone()}
+                />
+            </div>
+            {
+                if result.violations.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <ul class="invalid">
+                            { result.violations.iter().map(|violation| html! { <li>{ violation.to_string() }</li> }).collect::<Vec<Html>>() }
+                        </ul>
+                    }
+                }
+            }
+            <TabledDisplay<Vec<String>, Vec<Vec<String>>>
+                headers={ headers }
+                data={ Vec::new() }
+            >
+                { result.connections.iter().enumerate().map(|(index, row)| html! {
+                    <>
+                        <tr>
+                            <td>{ index + 1 }</td>
+                            { row.iter().map(|cell| html! {
+                                <td>{ self.element_name(matching_data, cell) }</td>
+                            }).collect::<Vec<Html>>() }
+                            { (row.len()..max_size).map(|_| html! { <td/> }).collect::<Vec<Html>>() }
+                        </tr>
+                        {
+                            match result.groups.get(index) {
+                                Some(group) => html! {
+                                    <tr class="breakdown">
+                                        <td colspan={ columns.to_string() }>
+                                            { self.view_group_score(matching_data, group) }
+                                        </td>
+                                    </tr>
+                                },
+                                None => html! {},
+                            }
+                        }
+                    </>
+                }).collect::<Vec<Html>>() }
+            </TabledDisplay<Vec<String>, Vec<Vec<String>>>>
+            {
+                if result.metrics.is_empty() {
+                    html! {}
+                } else {
+                    html! {
+                        <Collapsable header="Satisfaction">
+                            <MetricsTable
+                                metrics={result.metrics.clone()}
+                                labels={matching_data.elements.keys().map(|id| (id.clone(), self.element_name(matching_data, id))).collect::<HashMap<String, String>>()}
+                                sources={self.score_source_labels(matching_data)}
+                            />
+                        </Collapsable>
+                    }
+                }
+            }
+            <Collapsable header="Report">
+                <ReportExport
+                    number={number}
+                    result={result.clone()}
+                    matching_data={matching_data.clone()}
+                />
+            </Collapsable>
+        </div>
+        };
+    }
+
+    fn view_sensitivity(&self, matching_data: &MatchingData) -> Html {
+        return match &self.sensitivity {
+            Some(Ok(impacts)) => html! {
+                <SensitivityTable
+                    impacts={impacts.clone()}
+                    sources={self.score_source_labels(matching_data)}
+                    elements={matching_data.elements.len()}
+                />
+            },
+            Some(Err(error)) => html! { <pre class="invalid">{ error.to_string() }</pre> },
+            None => html! {},
+        };
+    }
+
+    /// Workbook with a sheet for every result, single results are exported with their own table.
+    fn view_export(&self, results: &[RunRecord]) -> Html {
+        let successful = successful_results(results);
+        if successful.len() < 2 {
+            return html! {};
+        }
+        return html! {
+            <div class="result">
+                <ResultExport results={successful} matching_data={self.matching_data().clone()}/>
+            </div>
+        };
+    }
+
+    fn view_comparison(&self, results: &[RunRecord]) -> Html {
+        let successful: Vec<(usize, MatchingResult, Sources)> = results
             .iter()
-            .map(|row| {
-                let mut res: Vec<String> = row.iter()
-                    .map(|cell| elements.get(cell)
-                        .expect(&*format!("{} is an element in a result set but could not be found in element list!", cell))
-                        .get(display_value_key)
-                        .expect("Failed to fetch display value from element!")
-                        .to_string()
-                    ).collect();
-                res.insert(0, format!("{}", counter));
-                counter += 1;
-                return res;
+            .enumerate()
+            .filter_map(|(index, record)| {
+                let result = record.outcome.as_ref().ok()?;
+                Some((index + 1, result.clone(), record.sources.clone()))
             })
             .collect();
+        if successful.len() < 2 {
+            return html! {};
+        }
+        let matching_data = self.matching_data();
+        return html! {
+            <Collapsable header="Compare results">
+                <ResultComparison
+                    results={successful}
+                    labels={matching_data.elements.keys().map(|id| (id.clone(), self.element_name(matching_data, id))).collect::<HashMap<String, String>>()}
+                />
+            </Collapsable>
+        };
+    }
+
+    /// Points of a group per rule or pair, expandable to the single connections they apply to.
+    fn view_group_score(&self, matching_data: &MatchingData, group: &GroupScore) -> Html {
+        return html! {
+            <details>
+                <summary>{ format!("Score {}", group.score) }</summary>
+                <ul>
+                    { group.totals().iter().map(|(source, total)| html! {
+                        <li>
+                            <details>
+                                <summary>{ format!("{:+} {}", total, self.score_source_label(matching_data, source)) }</summary>
+                                <ul>
+                                    { group.entries.iter().filter(|entry| entry.source == *source).map(|entry| html! {
+                                        <li>{ format!(
+                                            "{:+} {} -> {}",
+                                            entry.points,
+                                            self.element_name(matching_data, &entry.element),
+                                            self.element_name(matching_data, &entry.target)
+                                        ) }</li>
+                                    }).collect::<Vec<Html>>() }
+                                </ul>
+                            </details>
+                        </li>
+                    }).collect::<Vec<Html>>() }
+                </ul>
+            </details>
+        };
+    }
+
+    fn score_source_labels(&self, matching_data: &MatchingData) -> HashMap<ScoreSource, String> {
+        return (0..matching_data.rules.len())
+            .map(ScoreSource::Rule)
+            .chain((0..matching_data.pairs.len()).map(ScoreSource::Pair))
+            .chain((0..matching_data.layers.len()).map(ScoreSource::Layer))
+            .map(|source| (source, self.score_source_label(matching_data, &source)))
+            .collect();
+    }
+
+    fn score_source_label(&self, matching_data: &MatchingData, source: &ScoreSource) -> String {
+        return match source {
+            ScoreSource::Base => "Base score".to_string(),
+            ScoreSource::Rule(index) => match matching_data.rules.get(*index) {
+                Some(rule) => format!(
+                    "Rule {}: {}",
+                    index + 1,
+                    print_rule(rule, &matching_data.fields)
+                ),
+                None => format!("Rule {}", index + 1),
+            },
+            ScoreSource::Pair(index) => match matching_data.pairs.get(*index) {
+                Some(pair) => format!(
+                    "Pair {}: {} - {}",
+                    index + 1,
+                    matching_data.element_label(&pair.first),
+                    matching_data.element_label(&pair.second)
+                ),
+                None => format!("Pair {}", index + 1),
+            },
+            ScoreSource::Layer(index) => match matching_data.layers.get(*index) {
+                Some(layer) => format!("Score layer {}: {}", index + 1, layer.name),
+                None => format!("Score layer {}", index + 1),
+            },
+        };
+    }
+
+    /// Value of the name field of an element, or its id if it has none.
+    fn element_name(&self, matching_data: &MatchingData, id: &String) -> String {
+        return match matching_data
+            .name_field()
+            .and_then(|field| matching_data.elements.get(id)?.get(field))
+        {
+            Some(name) => name.to_string(),
+            None => id.clone(),
+        };
+    }
+
+    fn view_error(
+        &self,
+        ctx: &Context<Self>,
+        number: usize,
+        record: &RunRecord,
+        error: &MatchingError,
+    ) -> Html {
         return html! {
         <div class="result">
             <div class="result-header">
-                <div> { result.score } </div>
-                <button>{ "Download CSV" }</button>
+                <div> { format!("Result {}", number) } </div>
+                { self.view_run_info(ctx, number, record) }
             </div>
-            <TabledDisplay<Vec<String>, Vec<Vec<String>>>
-                headers={ headers }
-                data={ data }
-            />
+            <pre class="invalid">{ error.to_string() }</pre>
         </div>
         };
     }
 
+    /// When and how a result was computed, it can be reproduced as long as the data is unchanged.
+    fn view_run_info(&self, ctx: &Context<Self>, number: usize, record: &RunRecord) -> Html {
+        let kind = match record.kind {
+            RunKind::Process => "processed",
+            RunKind::Verify(_) => "verified grouping",
+        };
+        let unchanged = self.input_hash.as_ref() == Some(&record.input_hash);
+        return html! {
+            <div class="run-info" title={ format!("Input hash {}", record.input_hash) }>
+                { format!("{}, {}", kind, record.created) }
+                {
+                    if unchanged {
+                        html! {
+                            <button onclick={ctx.link().callback(move |_| BaseMsg::Reproduce(number - 1))}>
+                                { "Reproduce" }
+                            </button>
+                        }
+                    } else {
+                        html! { <span class="warning">{ "computed from different data" }</span> }
+                    }
+                }
+            </div>
+        };
+    }
+
     fn view_connection(&self, connections: &Vec<usize>) -> Html {
         return html! {
         <div class="connection">
@@ -204,3 +625,17 @@ where
         html! {}
     }
 }
+
+/// Successful results with their number, counted from 1 like in the result headers.
+fn successful_results(results: &[RunRecord]) -> Vec<(usize, MatchingResult)> {
+    return results
+        .iter()
+        .enumerate()
+        .filter_map(|(index, record)| Some((index + 1, record.outcome.as_ref().ok()?.clone())))
+        .collect();
+}
+
+/// Current time as ISO 8601 timestamp, taken from the browser.
+pub fn now() -> String {
+    return js_sys::Date::new_0().to_iso_string().into();
+}